@@ -0,0 +1,183 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::models::{Bim, ReadOptions};
+
+/// The outcome of checking whether a bim file is already sorted.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Paths of the collections whose items would be reordered by sorting,
+    /// e.g. `model.tables["Sales"].columns`.
+    pub unsorted: Vec<String>,
+
+    /// Whether the sorted output differs from the original contents.
+    pub differs: bool,
+}
+
+impl Report {
+    #[must_use]
+    pub const fn is_sorted(&self) -> bool {
+        !self.differs
+    }
+}
+
+/// Parses and sorts `contents` in memory, reporting what sorting would change.
+///
+/// # Errors
+/// Returns an error if `contents` is not a valid bim file or fails validation.
+pub fn check_str(contents: &str, options: ReadOptions) -> Result<Report> {
+    check(Bim::from_str_with(contents, options)?, contents)
+}
+
+/// Sorts `bim`, which was read from `contents`, reporting what sorting changed.
+///
+/// # Errors
+/// Returns an error if `bim` fails validation or cannot be serialized.
+pub fn check(mut bim: Bim, contents: &str) -> Result<Report> {
+    bim.validate()?;
+    let before = serde_json::to_value(&bim).map_err(Error::Serialize)?;
+    bim.sort();
+    let after = serde_json::to_value(&bim).map_err(Error::Serialize)?;
+
+    let mut unsorted = Vec::new();
+    unsorted_collections(&before, &after, "", &mut unsorted);
+
+    Ok(Report {
+        unsorted,
+        differs: bim.to_json()? != contents,
+    })
+}
+
+/// The name used to match up items of a collection before and after sorting.
+pub(crate) fn identity(value: &Value) -> Option<&str> {
+    value
+        .get("name")
+        .or_else(|| value.get("memberName"))
+        .and_then(Value::as_str)
+}
+
+fn unsorted_collections(before: &Value, after: &Value, path: &str, out: &mut Vec<String>) {
+    match (before, after) {
+        (Value::Object(b), Value::Object(a)) => {
+            for (key, value) in b {
+                if let Some(other) = a.get(key) {
+                    let child = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{path}.{key}")
+                    };
+                    unsorted_collections(value, other, &child, out);
+                }
+            }
+        }
+        (Value::Array(b), Value::Array(a)) => {
+            let b_names: Vec<_> = b.iter().map(identity).collect();
+            let a_names: Vec<_> = a.iter().map(identity).collect();
+            if b_names != a_names {
+                out.push(path.to_string());
+            }
+
+            for (index, value) in b.iter().enumerate() {
+                let (other, child) = identity(value).map_or_else(
+                    || (a.get(index), format!("{path}[{index}]")),
+                    |name| {
+                        (
+                            a.iter().find(|v| identity(v) == Some(name)),
+                            format!("{path}[{name:?}]"),
+                        )
+                    },
+                );
+                if let Some(other) = other {
+                    unsorted_collections(value, other, &child, out);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::models::test::bim_value;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn bim(tables: &Value) -> String {
+        serde_json::to_string_pretty(&bim_value(json!({"tables": tables}))).unwrap()
+    }
+
+    fn table(name: &str, columns: &[&str]) -> Value {
+        let columns: Vec<_> = columns
+            .iter()
+            .map(|c| json!({"name": c, "dataType": "string", "sourceColumn": c}))
+            .collect();
+        json!(
+            {
+                "name": name,
+                "columns": columns,
+                "partitions": []
+            }
+        )
+    }
+
+    #[test]
+    fn sorted_file_passes() {
+        let contents = bim(&json!([table("A", &["a", "b"]), table("B", &["c"])]));
+        let contents = Bim::from_str(&contents).unwrap().to_json().unwrap();
+
+        let report = check_str(&contents, ReadOptions::default()).unwrap();
+
+        assert!(report.is_sorted());
+        assert!(report.unsorted.is_empty());
+    }
+
+    #[test]
+    fn reports_unsorted_collections_by_name() {
+        let contents = bim(&json!([table("B", &["d", "c"]), table("A", &["a", "b"])]));
+
+        let report = check_str(&contents, ReadOptions::default()).unwrap();
+
+        assert!(!report.is_sorted());
+        assert_eq!(
+            report.unsorted,
+            vec![
+                "model.tables".to_string(),
+                "model.tables[\"B\"].columns".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn formatting_differences_fail_without_unsorted_collections() {
+        let contents = bim(&json!([table("A", &["a"])]));
+        let contents = Bim::from_str(&contents)
+            .unwrap()
+            .to_json()
+            .unwrap()
+            .replace("  ", "    ");
+
+        let report = check_str(&contents, ReadOptions::default()).unwrap();
+
+        assert!(!report.is_sorted());
+        assert!(report.unsorted.is_empty());
+    }
+}
@@ -15,7 +15,14 @@ GNU General Public License for more details.
 You should have received a copy of the GNU General Public License
 along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
-use clap::{command, Parser};
+use std::path::PathBuf;
+
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+
+use crate::models::format::Style;
+use crate::models::layout::{Layout, LineEnding};
+use crate::models::{ReadOptions, ScrubPolicy, WriteOptions};
 
 #[derive(Parser)]
 #[command(
@@ -23,10 +30,51 @@ use clap::{command, Parser};
     version,
     arg_required_else_help(true),
     about,
-    after_help(LONG_ABOUT)
+    after_help(LONG_ABOUT),
+    args_conflicts_with_subcommands(true)
 )]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Args {
-    pub file: Option<std::path::PathBuf>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Bim files, directories to search for bim files, or glob patterns.
+    /// Use `-` to read from stdin and write to stdout
+    pub files: Vec<PathBuf>,
+
+    #[arg(long)]
+    /// Read the bim file from stdin and write the sorted output to stdout
+    pub stdin: bool,
+
+    #[arg(long)]
+    /// Write the sorted output to stdout instead of modifying the file
+    pub stdout: bool,
+
+    #[arg(long)]
+    /// Report whether the file is sorted without rewriting it
+    pub check: bool,
+
+    #[command(flatten)]
+    pub write: WriteArgs,
+
+    #[command(flatten)]
+    pub read: ReadArgs,
+
+    #[arg(long, value_enum)]
+    /// Line endings to write [default: same as the input]
+    pub line_ending: Option<LineEndingArg>,
+
+    #[arg(long, value_name = "BOOL")]
+    /// Whether to write a UTF-8 byte order mark [default: same as the input]
+    pub bom: Option<bool>,
+
+    #[arg(long, value_name = "BOOL")]
+    /// Whether to end the file with a line ending [default: same as the input]
+    pub trailing_newline: Option<bool>,
+
+    #[arg(long, value_enum)]
+    /// JSON formatting style to write [default: same as the input]
+    pub style: Option<StyleArg>,
 
     #[arg(short)]
     /// Print terms and conditions
@@ -37,11 +85,319 @@ pub struct Args {
     pub warranty: bool,
 }
 
+/// Options for rewriting bim files in place, shared by the commands that do.
+#[derive(clap::Args)]
+pub struct WriteArgs {
+    #[arg(
+        long,
+        value_name = "SUFFIX",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = ".bak"
+    )]
+    /// Keep a copy of each original file, named with SUFFIX appended [default: .bak]
+    pub backup: Option<String>,
+}
+
+impl WriteArgs {
+    #[must_use]
+    pub fn options(&self) -> WriteOptions {
+        WriteOptions {
+            backup: self.backup.clone(),
+        }
+    }
+}
+
+/// Options for reading bim files, shared by every command that reads them.
+#[derive(clap::Args)]
+pub struct ReadArgs {
+    #[arg(long)]
+    /// Keep properties that are not modelled instead of rejecting the file
+    pub preserve_unknown: bool,
+}
+
+impl ReadArgs {
+    #[must_use]
+    pub const fn options(&self) -> ReadOptions {
+        ReadOptions {
+            preserve_unknown: self.preserve_unknown,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Report the tables, columns, measures and other objects that differ
+    /// between two bim files, exiting with 1 if there are any
+    Diff(DiffArgs),
+
+    /// Three-way merge bim files by object name, for use as a git merge
+    /// driver: `bim_sort merge %O %A %B %P`
+    Merge(MergeArgs),
+
+    /// Sort a bim file from FILE or stdin to stdout, for use as a git clean
+    /// filter or textconv driver. Input that cannot be sorted is passed
+    /// through unchanged with a warning
+    Filter(FilterArgs),
+
+    /// Register `filter` as the clean filter and textconv driver for bim
+    /// files in the current git repository
+    InstallGitFilter(InstallArgs),
+
+    /// Remove passwords, keys and tokens from the data sources of bim files
+    /// and redact usernames, rewriting the files in place
+    Scrub(ScrubArgs),
+
+    /// Rewrite the data source addresses, credential paths and named M
+    /// expressions of a bim file from an environment file
+    ApplyEnv(ApplyEnvArgs),
+
+    /// Write the data source addresses, credential paths and parameter
+    /// expressions of a bim file as an environment file for `apply-env`
+    ExtractEnv(ExtractEnvArgs),
+}
+
+#[derive(clap::Args)]
+pub struct DiffArgs {
+    /// The original bim file
+    pub old: PathBuf,
+
+    /// The changed bim file
+    pub new: PathBuf,
+
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    /// Output format
+    pub format: Format,
+
+    #[command(flatten)]
+    pub read: ReadArgs,
+}
+
+#[derive(clap::Args)]
+pub struct MergeArgs {
+    /// The common ancestor of the two versions (%O), empty if there is none
+    pub base: PathBuf,
+
+    /// Our version, overwritten with the merged result (%A)
+    pub ours: PathBuf,
+
+    /// Their version (%B)
+    pub theirs: PathBuf,
+
+    /// The path of the file being merged (%P). On a conflict their version
+    /// is copied next to it as `<PATH>.theirs` [default: OURS]
+    pub path: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub read: ReadArgs,
+
+    #[arg(long, value_enum)]
+    /// JSON formatting style to write [default: same as the input]
+    pub style: Option<StyleArg>,
+}
+
+#[derive(clap::Args)]
+pub struct FilterArgs {
+    /// The bim file to read, as given by git to textconv drivers
+    /// [default: stdin]
+    pub file: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub read: ReadArgs,
+
+    #[arg(long, value_enum)]
+    /// JSON formatting style to write [default: same as the input]
+    pub style: Option<StyleArg>,
+}
+
+#[derive(clap::Args)]
+pub struct InstallArgs {
+    #[arg(long)]
+    /// Also register `merge` as the merge driver for bim files
+    pub merge: bool,
+
+    #[arg(long)]
+    /// Keep properties that are not modelled when filtering and merging
+    pub preserve_unknown: bool,
+
+    #[arg(long, value_enum)]
+    /// JSON formatting style to write when filtering and merging
+    pub style: Option<StyleArg>,
+}
+
+impl InstallArgs {
+    /// The options to give `filter` and `merge` when git runs them.
+    #[must_use]
+    pub fn options(&self) -> Vec<String> {
+        let mut options = Vec::new();
+        if self.preserve_unknown {
+            options.push(String::from("--preserve-unknown"));
+        }
+        if let Some(style) = self.style.and_then(|s| s.to_possible_value()) {
+            options.push(String::from("--style"));
+            options.push(style.get_name().to_string());
+        }
+        options
+    }
+}
+
+#[derive(clap::Args)]
+pub struct ScrubArgs {
+    /// Bim files, directories to search for bim files, or glob patterns
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+
+    #[arg(long)]
+    /// Leave usernames and account names in place
+    pub keep_usernames: bool,
+
+    #[arg(long)]
+    /// Also redact server, database, url and path addresses, including
+    /// where they appear in data source names and M string literals
+    pub addresses: bool,
+
+    #[arg(long)]
+    /// Report any passwords, keys or tokens without rewriting the files,
+    /// exiting with 1 if there are any
+    pub check: bool,
+
+    #[command(flatten)]
+    pub read: ReadArgs,
+
+    #[command(flatten)]
+    pub write: WriteArgs,
+
+    #[arg(long, value_enum)]
+    /// JSON formatting style to write [default: same as the input]
+    pub style: Option<StyleArg>,
+}
+
+impl ScrubArgs {
+    #[must_use]
+    pub const fn policy(&self) -> ScrubPolicy {
+        ScrubPolicy {
+            usernames: !self.keep_usernames,
+            addresses: self.addresses,
+        }
+    }
+}
+
+#[derive(clap::Args)]
+pub struct ApplyEnvArgs {
+    /// The environment file, keyed by data source and expression name
+    pub env: PathBuf,
+
+    /// The bim file to rewrite
+    pub file: PathBuf,
+
+    #[command(flatten)]
+    pub read: ReadArgs,
+
+    #[arg(long, value_enum)]
+    /// JSON formatting style to write [default: same as the input]
+    pub style: Option<StyleArg>,
+}
+
+#[derive(clap::Args)]
+pub struct ExtractEnvArgs {
+    /// The bim file to read
+    pub file: PathBuf,
+
+    #[arg(long, short)]
+    /// Write the environment file here [default: stdout]
+    pub output: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub read: ReadArgs,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum LineEndingArg {
+    Lf,
+    Crlf,
+}
+
+impl From<LineEndingArg> for LineEnding {
+    fn from(arg: LineEndingArg) -> Self {
+        match arg {
+            LineEndingArg::Lf => Self::Lf,
+            LineEndingArg::Crlf => Self::CrLf,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum StyleArg {
+    /// Visual Studio, escaping non-ASCII characters and `<>&'`
+    Vs,
+    /// Tabular Editor
+    TabularEditor,
+    /// A single line without whitespace
+    Compact,
+    /// The pretty printer used by earlier versions
+    Serde,
+}
+
+impl From<StyleArg> for Style {
+    fn from(arg: StyleArg) -> Self {
+        match arg {
+            StyleArg::Vs => Self::VisualStudio,
+            StyleArg::TabularEditor => Self::TabularEditor,
+            StyleArg::Compact => Self::Compact,
+            StyleArg::Serde => Self::Serde,
+        }
+    }
+}
+
 impl Args {
     #[must_use]
     pub fn get() -> Self {
         Self::parse()
     }
+
+    /// Exits with a usage message for arguments that only conflict once the
+    /// file list is known, in the same way clap rejects arguments itself.
+    pub fn conflict(message: &str) -> ! {
+        Self::command()
+            .error(ErrorKind::ArgumentConflict, message)
+            .exit()
+    }
+
+    /// Exits with a usage message when the paths given name no bim files,
+    /// so a mistyped path or glob fails rather than passing silently.
+    pub fn no_files(paths: &[PathBuf]) -> ! {
+        let message = if paths.is_empty() {
+            String::from("no bim files given")
+        } else {
+            let paths: Vec<_> = paths.iter().map(|p| p.display().to_string()).collect();
+            format!("no bim files found in {}", paths.join(", "))
+        };
+        Self::command()
+            .error(ErrorKind::InvalidValue, message)
+            .exit()
+    }
+
+    /// Overrides the detected layout with any given on the command line.
+    pub fn apply_layout(&self, layout: &mut Layout) {
+        if let Some(style) = self.style {
+            layout.style = style.into();
+        }
+        if let Some(line_ending) = self.line_ending {
+            layout.line_ending = line_ending.into();
+        }
+        if let Some(bom) = self.bom {
+            layout.bom = bom;
+        }
+        if let Some(trailing_newline) = self.trailing_newline {
+            layout.trailing_newline = trailing_newline;
+        }
+    }
 }
 
 static LONG_ABOUT: &str = include_str!("about.txt");
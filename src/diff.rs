@@ -0,0 +1,550 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::models::expression::ModelExpression;
+use crate::models::roles::{Role, TablePermission};
+use crate::models::table::{Attributes, Column, Measure, Partition, Table};
+use crate::models::{Bim, DataSource, Expressive, Relationship};
+
+/// The differences between two bim files, by object name.
+#[derive(Serialize, Debug, Default, PartialEq, Eq)]
+pub struct Diff {
+    pub changes: Vec<Change>,
+}
+
+impl Diff {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Kind {
+    Model,
+    DataSource,
+    Table,
+    Column,
+    Measure,
+    Partition,
+    Relationship,
+    Role,
+    TablePermission,
+    Expression,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Status {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// An object that was added, removed or changed.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct Change {
+    pub status: Status,
+    pub kind: Kind,
+
+    /// The object's name, qualified by its table for columns, measures and
+    /// partitions, e.g. `Sales[Amount]`, or by its role for table
+    /// permissions.
+    pub name: String,
+
+    /// The properties that changed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<String>,
+
+    /// A line diff of the object's DAX or M expression, if it changed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub expression: Vec<Line>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(tag = "op", content = "line", rename_all = "camelCase")]
+pub enum Line {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+/// Compares two bim files object by object.
+///
+/// # Errors
+/// Returns [`Error::Serialize`] if either model cannot be represented as JSON.
+pub fn diff(old: &Bim, new: &Bim) -> Result<Diff> {
+    let mut changes = Vec::new();
+    let (old_model, new_model) = (&old.model, &new.model);
+
+    let mut properties = changed_properties(old, new, &["model"])?;
+    properties.extend(changed_properties(
+        old_model,
+        new_model,
+        &[
+            "dataSources",
+            "tables",
+            "relationships",
+            "roles",
+            "expressions",
+        ],
+    )?);
+    if !properties.is_empty() {
+        changes.push(Change {
+            status: Status::Changed,
+            kind: Kind::Model,
+            name: old.name.clone(),
+            properties,
+            expression: Vec::new(),
+        });
+    }
+
+    compare(
+        &mut changes,
+        Kind::DataSource,
+        None,
+        &old_model.data_sources,
+        &new_model.data_sources,
+    )?;
+    let tables = compare(
+        &mut changes,
+        Kind::Table,
+        None,
+        &old_model.tables,
+        &new_model.tables,
+    )?;
+    for (old, new) in tables {
+        let table = Some(old.name.as_str());
+        compare(
+            &mut changes,
+            Kind::Column,
+            table,
+            &old.columns,
+            &new.columns,
+        )?;
+        compare(
+            &mut changes,
+            Kind::Measure,
+            table,
+            old.measures.as_deref().unwrap_or_default(),
+            new.measures.as_deref().unwrap_or_default(),
+        )?;
+        compare(
+            &mut changes,
+            Kind::Partition,
+            table,
+            &old.partitions,
+            &new.partitions,
+        )?;
+    }
+    compare(
+        &mut changes,
+        Kind::Relationship,
+        None,
+        &old_model.relationships,
+        &new_model.relationships,
+    )?;
+    let roles = compare(
+        &mut changes,
+        Kind::Role,
+        None,
+        old_model.roles.as_deref().unwrap_or_default(),
+        new_model.roles.as_deref().unwrap_or_default(),
+    )?;
+    for (old, new) in roles {
+        compare(
+            &mut changes,
+            Kind::TablePermission,
+            Some(old.name.as_str()),
+            old.table_permissions.as_deref().unwrap_or_default(),
+            new.table_permissions.as_deref().unwrap_or_default(),
+        )?;
+    }
+    compare(
+        &mut changes,
+        Kind::Expression,
+        None,
+        &old_model.expressions,
+        &new_model.expressions,
+    )?;
+
+    Ok(Diff { changes })
+}
+
+/// An object that is compared by name.
+trait Item: Serialize {
+    fn name(&self) -> String;
+
+    /// Properties holding child objects that are compared separately.
+    fn children() -> &'static [&'static str] {
+        &[]
+    }
+
+    fn expression(&self) -> Option<String> {
+        None
+    }
+}
+
+impl Item for DataSource {
+    fn name(&self) -> String {
+        Self::name(self).to_string()
+    }
+}
+
+impl Item for Table {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn children() -> &'static [&'static str] {
+        &["columns", "measures", "partitions"]
+    }
+}
+
+impl Item for Column {
+    fn name(&self) -> String {
+        Attributes::name(self)
+    }
+
+    fn expression(&self) -> Option<String> {
+        Expressive::expression(self)
+    }
+}
+
+impl Item for Measure {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn expression(&self) -> Option<String> {
+        Expressive::expression(self)
+    }
+}
+
+impl Item for Partition {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn expression(&self) -> Option<String> {
+        self.source.expression()
+    }
+}
+
+impl Item for Relationship {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+impl Item for Role {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn children() -> &'static [&'static str] {
+        &["tablePermissions"]
+    }
+}
+
+impl Item for TablePermission {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn expression(&self) -> Option<String> {
+        Expressive::expression(self)
+    }
+}
+
+impl Item for ModelExpression {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn expression(&self) -> Option<String> {
+        Expressive::expression(self)
+    }
+}
+
+/// Records the changes between two collections, returning the pairs of
+/// objects present in both. Objects are paired by name ignoring case, so a
+/// rename that only changes case is a change to the `name` property.
+fn compare<'a, T: Item>(
+    changes: &mut Vec<Change>,
+    kind: Kind,
+    table: Option<&str>,
+    old: &'a [T],
+    new: &'a [T],
+) -> Result<Vec<(&'a T, &'a T)>> {
+    let qualify = |name: String| match table {
+        Some(table) => format!("{table}[{name}]"),
+        None => name,
+    };
+    let change = |status, name| Change {
+        status,
+        kind,
+        name: qualify(name),
+        properties: Vec::new(),
+        expression: Vec::new(),
+    };
+
+    let same_name = |a: &T, b: &T| a.name().to_lowercase() == b.name().to_lowercase();
+
+    let mut matched = Vec::new();
+    for item in old {
+        match new.iter().find(|n| same_name(n, item)) {
+            None => changes.push(change(Status::Removed, item.name())),
+            Some(other) => {
+                let properties = changed_properties(item, other, T::children())?;
+                if !properties.is_empty() {
+                    let (before, after) = (item.expression(), other.expression());
+                    changes.push(Change {
+                        properties,
+                        expression: if before == after {
+                            Vec::new()
+                        } else {
+                            line_diff(
+                                before.as_deref().unwrap_or_default(),
+                                after.as_deref().unwrap_or_default(),
+                            )
+                        },
+                        ..change(Status::Changed, item.name())
+                    });
+                }
+                matched.push((item, other));
+            }
+        }
+    }
+    for item in new {
+        if !old.iter().any(|o| same_name(o, item)) {
+            changes.push(change(Status::Added, item.name()));
+        }
+    }
+    Ok(matched)
+}
+
+/// Lists the top level properties that differ, other than those in `skip`.
+fn changed_properties<T: Serialize>(old: &T, new: &T, skip: &[&str]) -> Result<Vec<String>> {
+    let old = serde_json::to_value(old).map_err(Error::Serialize)?;
+    let new = serde_json::to_value(new).map_err(Error::Serialize)?;
+    let (Value::Object(old), Value::Object(new)) = (old, new) else {
+        return Ok(Vec::new());
+    };
+
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    Ok(keys
+        .into_iter()
+        .filter(|key| !skip.contains(&key.as_str()) && old.get(*key) != new.get(*key))
+        .cloned()
+        .collect())
+}
+
+/// A line diff using the longest common subsequence of lines.
+fn line_diff(old: &str, new: &str) -> Vec<Line> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let mut lcs = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(Line::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(Line::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(Line::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    lines
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            Self::Model => "model",
+            Self::DataSource => "data source",
+            Self::Table => "table",
+            Self::Column => "column",
+            Self::Measure => "measure",
+            Self::Partition => "partition",
+            Self::Relationship => "relationship",
+            Self::Role => "role",
+            Self::TablePermission => "table permission",
+            Self::Expression => "expression",
+        };
+        write!(f, "{kind}")
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self.status {
+            Status::Added => '+',
+            Status::Removed => '-',
+            Status::Changed => '~',
+        };
+        write!(f, "{status} {} {}", self.kind, self.name)?;
+        if !self.properties.is_empty() {
+            write!(f, " ({})", self.properties.join(", "))?;
+        }
+        for line in &self.expression {
+            match line {
+                Line::Same(l) => write!(f, "\n      {l}")?,
+                Line::Added(l) => write!(f, "\n    + {l}")?,
+                Line::Removed(l) => write!(f, "\n    - {l}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::models::test::bim_value;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn bim(tables: &Value) -> Bim {
+        Bim::from_str(&bim_value(json!({"tables": tables})).to_string()).unwrap()
+    }
+
+    fn sales(measures: &Value) -> Value {
+        json!(
+            {
+                "name": "Sales",
+                "columns": [{"name": "Amount", "dataType": "double", "sourceColumn": "Amount"}],
+                "partitions": [],
+                "measures": measures
+            }
+        )
+    }
+
+    #[test]
+    fn identical_files_have_no_changes() {
+        let old = bim(&json!([sales(&json!([]))]));
+
+        assert!(diff(&old, &old).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reports_added_and_removed_objects_by_name() {
+        let old = bim(&json!([sales(
+            &json!([{"name": "Total", "expression": "1"}])
+        )]));
+        let new = bim(&json!([
+            sales(&json!([])),
+            {"name": "Returns", "columns": [], "partitions": []}
+        ]));
+
+        let changes = diff(&old, &new).unwrap().changes;
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            (changes[0].status, changes[0].kind, changes[0].name.as_str()),
+            (Status::Added, Kind::Table, "Returns")
+        );
+        assert_eq!(
+            (changes[1].status, changes[1].kind, changes[1].name.as_str()),
+            (Status::Removed, Kind::Measure, "Sales[Total]")
+        );
+    }
+
+    #[test]
+    fn renames_that_only_change_case_are_changes() {
+        let old = bim(&json!([sales(
+            &json!([{"name": "Total", "expression": "1"}])
+        )]));
+        let new = bim(&json!([sales(
+            &json!([{"name": "TOTAL", "expression": "1"}])
+        )]));
+
+        let changes = diff(&old, &new).unwrap().changes;
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            (changes[0].status, changes[0].kind, changes[0].name.as_str()),
+            (Status::Changed, Kind::Measure, "Sales[Total]")
+        );
+        assert_eq!(changes[0].properties, vec!["name"]);
+    }
+
+    #[test]
+    fn changed_expressions_are_diffed_by_line() {
+        let old = bim(&json!([sales(&json!([{
+            "name": "Total",
+            "expression": ["CALCULATE (", "    SUM ( Sales[Amount] )", ")"]
+        }]))]));
+        let new = bim(&json!([sales(&json!([{
+            "name": "Total",
+            "expression": ["CALCULATE (", "    SUM ( Sales[Net] )", ")"],
+            "formatString": "0.00"
+        }]))]));
+
+        let changes = diff(&old, &new).unwrap().changes;
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].properties, vec!["expression", "formatString"]);
+        assert_eq!(
+            changes[0].expression,
+            vec![
+                Line::Same("CALCULATE (".to_string()),
+                Line::Removed("    SUM ( Sales[Amount] )".to_string()),
+                Line::Added("    SUM ( Sales[Net] )".to_string()),
+                Line::Same(")".to_string()),
+            ]
+        );
+        assert_eq!(
+            changes[0].to_string(),
+            "~ measure Sales[Total] (expression, formatString)\n      CALCULATE (\n    -     SUM ( Sales[Amount] )\n    +     SUM ( Sales[Net] )\n      )"
+        );
+    }
+}
@@ -0,0 +1,312 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::models::datasource::{Credential, DataSource};
+use crate::models::unknown::preserving_unknown;
+use crate::models::{Bim, Expression, ParseError, ReadOptions};
+
+/// The values of a model that differ between environments, e.g. dev, test
+/// and prod, keyed by data source and expression name.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Environment {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub data_sources: BTreeMap<String, DataSourceEnvironment>,
+
+    /// Named M expressions, usually parameters such as
+    /// `"localhost" meta [IsParameterQuery=true, ...]`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub expressions: BTreeMap<String, Expression>,
+}
+
+/// The connection of one data source. Structured data sources take an
+/// `address` and `credentialPath`, provider data sources a
+/// `connectionString`.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DataSourceEnvironment {
+    /// The `address` of the connection details, in the shape its protocol
+    /// uses, e.g. `{"server": "localhost", "database": "Sales"}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Value>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_path: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection_string: Option<String>,
+}
+
+impl Environment {
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not a valid
+    /// environment file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        serde_json::from_str(&contents).map_err(|e| Error::Parse(ParseError::from(e)))
+    }
+
+    /// # Errors
+    /// Returns an error if an address cannot be represented as JSON.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map(|json| json + "\n")
+            .map_err(Error::Serialize)
+    }
+
+    /// Extracts the addresses and credential paths of every data source and
+    /// the parameter expressions of `bim`.
+    ///
+    /// # Errors
+    /// Returns an error if an address cannot be represented as JSON.
+    pub fn extract(bim: &Bim) -> Result<Self> {
+        let mut environment = Self::default();
+
+        for data_source in &bim.model.data_sources {
+            let entry = match data_source {
+                DataSource::Structured(s) => {
+                    let details =
+                        serde_json::to_value(&s.connection_details).map_err(Error::Serialize)?;
+                    DataSourceEnvironment {
+                        address: details.get("address").cloned(),
+                        credential_path: Some(s.credential.path()),
+                        connection_string: None,
+                    }
+                }
+                DataSource::Provider(p) => DataSourceEnvironment {
+                    connection_string: Some(p.connection_string.clone()),
+                    ..DataSourceEnvironment::default()
+                },
+            };
+            environment
+                .data_sources
+                .insert(data_source.name().to_string(), entry);
+        }
+
+        for expression in bim.model.expressions.iter().filter(|e| e.is_parameter()) {
+            environment
+                .expressions
+                .insert(expression.name.clone(), expression.raw_expression().clone());
+        }
+        Ok(environment)
+    }
+
+    /// Rewrites the data sources and expressions of `bim` named in the
+    /// environment. Nothing is changed if any name is missing from the model
+    /// or a value does not suit its data source.
+    ///
+    /// # Errors
+    /// Returns a validation error listing the mismatches, or a parse error if
+    /// an address does not fit the protocol of its data source.
+    pub fn apply(&self, bim: &mut Bim, options: ReadOptions) -> Result<()> {
+        let problems = self.problems(bim);
+        if !problems.is_empty() {
+            return Err(Error::Validation(problems));
+        }
+
+        let mut data_sources = Vec::new();
+        for data_source in &bim.model.data_sources {
+            data_sources.push(match self.data_sources.get(data_source.name()) {
+                Some(entry) => Some(entry.applied_to(data_source, options)?),
+                None => None,
+            });
+        }
+        for (data_source, applied) in bim.model.data_sources.iter_mut().zip(data_sources) {
+            if let Some(applied) = applied {
+                *data_source = applied;
+            }
+        }
+
+        for expression in &mut bim.model.expressions {
+            if let Some(value) = self.expressions.get(&expression.name) {
+                expression.set_expression(value.clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn problems(&self, bim: &Bim) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        for (name, entry) in &self.data_sources {
+            match bim.model.data_sources.iter().find(|d| d.name() == name) {
+                None => problems.push(format!("data source {name:?} is not in the model")),
+                Some(DataSource::Structured(_)) if entry.connection_string.is_some() => {
+                    problems.push(format!(
+                        "data source {name:?} is structured and has no connectionString"
+                    ));
+                }
+                Some(DataSource::Provider(_))
+                    if entry.address.is_some() || entry.credential_path.is_some() =>
+                {
+                    problems.push(format!(
+                        "data source {name:?} is a provider and has no address or credentialPath"
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+
+        for name in self.expressions.keys() {
+            if !bim.model.expressions.iter().any(|e| &e.name == name) {
+                problems.push(format!("expression {name:?} is not in the model"));
+            }
+        }
+        problems
+    }
+}
+
+impl DataSourceEnvironment {
+    fn applied_to(&self, data_source: &DataSource, options: ReadOptions) -> Result<DataSource> {
+        let mut value = serde_json::to_value(data_source).map_err(Error::Serialize)?;
+
+        if let Some(address) = &self.address {
+            value["connectionDetails"]["address"] = address.clone();
+        }
+        if let Some(path) = &self.credential_path {
+            value["credential"]["path"] = Value::from(path.as_str());
+        }
+        if let Some(connection_string) = &self.connection_string {
+            value["connectionString"] = Value::from(connection_string.as_str());
+        }
+
+        preserving_unknown(options.preserve_unknown, || serde_json::from_value(value))
+            .map_err(|e| Error::Parse(ParseError::from(e)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::models::test::bim_value;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn bim() -> Bim {
+        let value = bim_value(json!(
+            {
+                "dataSources": [
+                    {
+                        "type": "structured",
+                        "name": "SQL/Sales",
+                        "connectionDetails": {
+                            "protocol": "tds",
+                            "address": {"server": "dev-sql", "database": "Sales"},
+                            "authentication": null,
+                            "query": null
+                        },
+                        "credential": {
+                            "AuthenticationKind": "Windows",
+                            "kind": "SQL",
+                            "path": "dev-sql;Sales"
+                        }
+                    },
+                    {
+                        "type": "provider",
+                        "name": "Warehouse",
+                        "connectionString": "Data Source=dev-sql;Initial Catalog=Warehouse"
+                    }
+                ],
+                "expressions": [
+                    {
+                        "name": "Server",
+                        "kind": "m",
+                        "expression": "\"dev-sql\" meta [IsParameterQuery=true, Type=\"Text\", IsParameterQueryRequired=true]"
+                    },
+                    {
+                        "name": "Rates",
+                        "kind": "m",
+                        "expression": ["let", "    Source = Web.Contents(\"https://example.com\")", "in", "    Source"]
+                    }
+                ]
+            }
+        ));
+        Bim::from_str(&value.to_string()).unwrap()
+    }
+
+    fn prod() -> Environment {
+        serde_json::from_value(json!(
+            {
+                "dataSources": {
+                    "SQL/Sales": {
+                        "address": {"server": "prod-sql", "database": "Sales"},
+                        "credentialPath": "prod-sql;Sales"
+                    },
+                    "Warehouse": {
+                        "connectionString": "Data Source=prod-sql;Initial Catalog=Warehouse"
+                    }
+                },
+                "expressions": {
+                    "Server": "\"prod-sql\" meta [IsParameterQuery=true, Type=\"Text\", IsParameterQueryRequired=true]"
+                }
+            }
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn applying_then_extracting_gives_the_environment_back() {
+        let mut bim = bim();
+
+        prod().apply(&mut bim, ReadOptions::default()).unwrap();
+
+        assert_eq!(Environment::extract(&bim).unwrap(), prod());
+    }
+
+    #[test]
+    fn extract_skips_expressions_that_are_not_parameters() {
+        let environment = Environment::extract(&bim()).unwrap();
+
+        let names: Vec<_> = environment.expressions.keys().collect();
+        assert_eq!(names, ["Server"]);
+    }
+
+    #[test]
+    fn unknown_names_are_rejected_without_changes() {
+        let mut bim = bim();
+        let mut environment = prod();
+        environment
+            .expressions
+            .insert(String::from("Database"), Expression::String(String::new()));
+        environment.data_sources.insert(
+            String::from("Warehouse"),
+            DataSourceEnvironment {
+                credential_path: Some(String::from("prod-sql")),
+                ..DataSourceEnvironment::default()
+            },
+        );
+
+        let error = environment
+            .apply(&mut bim, ReadOptions::default())
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "invalid model: data source \"Warehouse\" is a provider and has no address or credentialPath; expression \"Database\" is not in the model"
+        );
+        assert_eq!(bim, self::bim());
+    }
+}
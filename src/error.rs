@@ -0,0 +1,76 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::fmt;
+use std::io;
+
+use crate::models::ParseError;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Everything that can go wrong reading, sorting or writing a bim file.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file failed.
+    Io(io::Error),
+
+    /// The input is not valid JSON or does not match the bim file structure.
+    Parse(ParseError),
+
+    /// The model parsed but is internally inconsistent.
+    Validation(Vec<String>),
+
+    /// The model could not be converted back to JSON.
+    Serialize(serde_json::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read or write file: {e}"),
+            Self::Parse(e) => write!(f, "invalid bim file: {e}"),
+            Self::Validation(problems) => {
+                write!(f, "invalid model: {}", problems.join("; "))
+            }
+            Self::Serialize(e) => write!(f, "could not serialize model, nothing was written: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Validation(_) => None,
+            Self::Serialize(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
@@ -0,0 +1,127 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const EXTENSION: &str = "bim";
+
+/// Expands the paths given on the command line into the bim files they refer to.
+///
+/// Files are used as given, directories are searched recursively for `*.bim`
+/// files and anything else is treated as a glob pattern. The result is sorted
+/// and free of duplicates.
+///
+/// # Errors
+/// Returns an error if a directory cannot be read, a glob pattern is invalid
+/// or a path matches nothing.
+pub fn expand(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        if path.is_file() {
+            files.push(path.clone());
+        } else if path.is_dir() {
+            find_bim_files(path, &mut files)?;
+        } else {
+            let matched = glob_files(path)?;
+            if matched.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{}: no such file, directory or pattern", path.display()),
+                ));
+            }
+            files.extend(matched);
+        }
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn is_bim_file(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|e| e == EXTENSION)
+}
+
+fn find_bim_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_bim_files(&path, files)?;
+        } else if is_bim_file(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn glob_files(pattern: &Path) -> io::Result<Vec<PathBuf>> {
+    let invalid = |e: glob::PatternError| io::Error::new(io::ErrorKind::InvalidInput, e);
+
+    let mut files = Vec::new();
+    for entry in glob::glob(&pattern.to_string_lossy()).map_err(invalid)? {
+        let path = entry.map_err(io::Error::from)?;
+        if path.is_dir() {
+            find_bim_files(&path, &mut files)?;
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::models::test::TempDir;
+
+    #[test]
+    fn directories_are_searched_recursively_for_bim_files() {
+        let dir = TempDir::new("recursive");
+        let first = dir.touch("a/Model.bim");
+        let second = dir.touch("b/c/Model.bim");
+        dir.touch("b/notes.txt");
+
+        let files = expand(&[dir.path().to_path_buf()]).unwrap();
+
+        assert_eq!(files, vec![first, second]);
+    }
+
+    #[test]
+    fn globs_and_files_are_deduplicated() {
+        let dir = TempDir::new("glob");
+        let first = dir.touch("First.bim");
+        let second = dir.touch("Second.bim");
+
+        let pattern = dir.path().join("*.bim");
+        let files = expand(&[first.clone(), pattern]).unwrap();
+
+        assert_eq!(files, vec![first, second]);
+    }
+
+    #[test]
+    fn unmatched_patterns_are_an_error() {
+        let dir = TempDir::new("unmatched");
+
+        let result = expand(&[dir.path().join("*.bim")]);
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,244 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::Result;
+use crate::models::format::Style;
+use crate::models::{Bim, ReadOptions};
+
+/// The name the filter, diff and merge drivers are registered under.
+const DRIVER: &str = "bim";
+
+/// Sorts `contents` for a git clean filter or textconv driver, which should
+/// fall back to passing `contents` through unchanged on error.
+///
+/// # Errors
+/// Returns an error if `contents` is not UTF-8 or not a valid bim file.
+pub fn filter(contents: &[u8], options: ReadOptions, style: Option<Style>) -> Result<String> {
+    let contents =
+        std::str::from_utf8(contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut bim = Bim::from_str_with(contents, options)?;
+    if let Some(style) = style {
+        bim.layout.style = style;
+    }
+    bim.validate()?;
+    bim.sort();
+    bim.to_json()
+}
+
+/// What to register with git.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Install {
+    /// The program git runs, e.g. `bim_sort`.
+    pub program: String,
+
+    /// Options given to both the filter and the merge driver, e.g.
+    /// `--style vs`.
+    pub options: Vec<String>,
+
+    /// Also register `bim_sort merge` as the merge driver.
+    pub merge: bool,
+}
+
+impl Install {
+    /// The `.gitattributes` line routing bim files through the drivers.
+    #[must_use]
+    pub fn attributes(&self) -> String {
+        let merge = if self.merge {
+            format!(" merge={DRIVER}")
+        } else {
+            String::new()
+        };
+        format!("*.bim filter={DRIVER} diff={DRIVER}{merge}")
+    }
+
+    /// The git config entries, as `(key, value)` pairs.
+    #[must_use]
+    pub fn config(&self) -> Vec<(String, String)> {
+        let filter = self.command("filter", &[]);
+
+        let mut config = vec![
+            (format!("filter.{DRIVER}.clean"), filter.clone()),
+            (format!("diff.{DRIVER}.textconv"), filter),
+        ];
+        if self.merge {
+            config.push((
+                format!("merge.{DRIVER}.name"),
+                String::from("bim_sort object merge"),
+            ));
+            config.push((
+                format!("merge.{DRIVER}.driver"),
+                self.command("merge", &["%O", "%A", "%B", "%P"]),
+            ));
+        }
+        config
+    }
+
+    fn command(&self, subcommand: &str, args: &[&str]) -> String {
+        let mut command = vec![self.program.as_str(), subcommand];
+        command.extend(self.options.iter().map(String::as_str));
+        command.extend(args);
+        command.join(" ")
+    }
+
+    /// Writes the attributes to `.gitattributes` and the config entries to
+    /// the local config of the repository containing the working directory.
+    /// Returns the path of `.gitattributes`.
+    ///
+    /// # Errors
+    /// Returns an error if git cannot be run, the working directory is not in
+    /// a repository, or `.gitattributes` cannot be written.
+    pub fn run(&self) -> Result<PathBuf> {
+        let root = git(&["rev-parse", "--show-toplevel"])?;
+        let attributes = Path::new(root.trim()).join(".gitattributes");
+        self.write_attributes(&attributes)?;
+
+        for (key, value) in self.config() {
+            git(&["config", "--local", &key, &value])?;
+        }
+        Ok(attributes)
+    }
+
+    /// Adds the attributes to the `*.bim` line of `path`, replacing any
+    /// `filter`, `diff` or `merge` attributes already there and keeping the
+    /// rest. The file is created if needed.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or written.
+    pub fn write_attributes(&self, path: &Path) -> Result<()> {
+        let existing = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let ours = self.attributes();
+        let mut found = false;
+        let mut lines: Vec<String> = existing
+            .lines()
+            .map(|line| {
+                let mut tokens = line.split_whitespace();
+                if tokens.next() != Some("*.bim") {
+                    return line.to_string();
+                }
+                found = true;
+                let kept = tokens.filter(|token| !is_driver_attribute(token));
+                std::iter::once(ours.as_str())
+                    .chain(kept)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+        if !found {
+            lines.push(ours);
+        }
+
+        fs::write(path, lines.join("\n") + "\n")?;
+        Ok(())
+    }
+}
+
+/// Whether an attribute sets, unsets or unspecifies `filter`, `diff` or `merge`.
+fn is_driver_attribute(token: &str) -> bool {
+    let name = token.trim_start_matches(['-', '!']);
+    let name = name.split('=').next().unwrap_or(name);
+    matches!(name, "filter" | "diff" | "merge")
+}
+
+fn git(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git").args(args).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::models::test::TempDir;
+
+    fn install(merge: bool) -> Install {
+        Install {
+            program: String::from("bim_sort"),
+            options: Vec::new(),
+            merge,
+        }
+    }
+
+    #[test]
+    fn filter_rejects_invalid_input() {
+        assert!(filter(b"not json", ReadOptions::default(), None).is_err());
+    }
+
+    #[test]
+    fn filter_rejects_input_that_is_not_utf8() {
+        assert!(filter(b"{\"name\": \"\xff\"}", ReadOptions::default(), None).is_err());
+    }
+
+    #[test]
+    fn attributes_update_existing_bim_line() {
+        let dir = TempDir::new("gitattributes");
+        let path = dir.path().join(".gitattributes");
+        fs::write(&path, "*.txt text\n*.bim -text -diff\n").unwrap();
+
+        install(true).write_attributes(&path).unwrap();
+        install(true).write_attributes(&path).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "*.txt text\n*.bim filter=bim diff=bim merge=bim -text\n"
+        );
+    }
+
+    #[test]
+    fn config_registers_filter_and_textconv() {
+        let config = install(false).config();
+
+        assert_eq!(
+            config,
+            vec![
+                (
+                    String::from("filter.bim.clean"),
+                    String::from("bim_sort filter")
+                ),
+                (
+                    String::from("diff.bim.textconv"),
+                    String::from("bim_sort filter")
+                ),
+            ]
+        );
+        let options = Install {
+            options: vec![String::from("--style"), String::from("vs")],
+            ..install(true)
+        };
+        assert_eq!(options.config()[0].1, "bim_sort filter --style vs");
+        assert_eq!(
+            options.config()[3].1,
+            "bim_sort merge --style vs %O %A %B %P"
+        );
+    }
+}
@@ -15,6 +15,14 @@ GNU General Public License for more details.
 You should have received a copy of the GNU General Public License
 along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
+// Every module apart from `models` is held to the pedantic lints.
+#[warn(
+    clippy::pedantic,
+    clippy::nursery,
+    clippy::unwrap_used,
+    clippy::expect_used
+)]
+pub mod check;
 #[warn(
     clippy::pedantic,
     clippy::nursery,
@@ -22,4 +30,48 @@ along with this program.  If not, see <https://www.gnu.org/licenses/>.
     clippy::expect_used
 )]
 pub mod cli;
+#[warn(
+    clippy::pedantic,
+    clippy::nursery,
+    clippy::unwrap_used,
+    clippy::expect_used
+)]
+pub mod diff;
+#[warn(
+    clippy::pedantic,
+    clippy::nursery,
+    clippy::unwrap_used,
+    clippy::expect_used
+)]
+pub mod env;
+#[warn(
+    clippy::pedantic,
+    clippy::nursery,
+    clippy::unwrap_used,
+    clippy::expect_used
+)]
+pub mod error;
+#[warn(
+    clippy::pedantic,
+    clippy::nursery,
+    clippy::unwrap_used,
+    clippy::expect_used
+)]
+pub mod files;
+#[warn(
+    clippy::pedantic,
+    clippy::nursery,
+    clippy::unwrap_used,
+    clippy::expect_used
+)]
+pub mod git;
+#[warn(
+    clippy::pedantic,
+    clippy::nursery,
+    clippy::unwrap_used,
+    clippy::expect_used
+)]
+pub mod merge;
 pub mod models;
+
+pub use error::{Error, Result};
@@ -15,27 +15,375 @@ GNU General Public License for more details.
 You should have received a copy of the GNU General Public License
 along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
-use bim_sort::cli::{print_tc, print_warranty, Args};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use bim_sort::check::{self, Report};
+use bim_sort::cli::{
+    print_tc, print_warranty, ApplyEnvArgs, Args, Command, DiffArgs, ExtractEnvArgs, FilterArgs,
+    Format, InstallArgs, MergeArgs, ScrubArgs,
+};
+use bim_sort::env::Environment;
+use bim_sort::files;
+use bim_sort::git::{self, Install};
 use bim_sort::models::Bim;
+use bim_sort::{diff, merge};
+use bim_sort::{Error, Result};
+
+/// A file was not sorted in `--check` mode.
+const EXIT_UNSORTED: u8 = 1;
+/// The files given to `diff` differ.
+const EXIT_DIFFERENT: u8 = 1;
+/// The same object changed on both sides of a merge.
+const EXIT_CONFLICT: u8 = 1;
+/// `scrub --check` found secrets.
+const EXIT_SECRETS: u8 = 1;
+const EXIT_IO: u8 = 3;
+const EXIT_PARSE: u8 = 4;
+const EXIT_VALIDATION: u8 = 5;
+const EXIT_SERIALIZE: u8 = 6;
+
+const fn exit_code(error: &Error) -> u8 {
+    match error {
+        Error::Io(_) => EXIT_IO,
+        Error::Parse(_) => EXIT_PARSE,
+        Error::Validation(_) => EXIT_VALIDATION,
+        Error::Serialize(_) => EXIT_SERIALIZE,
+    }
+}
+
+enum Input {
+    Stdin,
+    File(PathBuf),
+}
+
+impl Input {
+    fn name(&self) -> String {
+        match self {
+            Self::Stdin => String::from("<stdin>"),
+            Self::File(path) => path.display().to_string(),
+        }
+    }
+
+    fn read(&self) -> Result<String> {
+        match self {
+            Self::Stdin => {
+                let mut contents = String::new();
+                io::stdin().read_to_string(&mut contents)?;
+                Ok(contents)
+            }
+            Self::File(path) => Ok(fs::read_to_string(path)?),
+        }
+    }
+
+    fn read_bytes(&self) -> Result<Vec<u8>> {
+        match self {
+            Self::Stdin => {
+                let mut contents = Vec::new();
+                io::stdin().read_to_end(&mut contents)?;
+                Ok(contents)
+            }
+            Self::File(path) => Ok(fs::read(path)?),
+        }
+    }
+}
+
+enum Outcome {
+    Sorted,
+    Unchanged,
+    Unsorted(Report),
+}
 
-#[allow(clippy::missing_errors_doc)]
-fn main() -> std::io::Result<()> {
+fn main() -> ExitCode {
     let args = Args::get();
 
     if args.conditions {
         print_tc();
-        return Ok(());
+        return ExitCode::SUCCESS;
     }
 
     if args.warranty {
         print_warranty();
-        return Ok(());
+        return ExitCode::SUCCESS;
+    }
+
+    let result = match &args.command {
+        Some(Command::Diff(diff_args)) => diff(diff_args),
+        Some(Command::Merge(merge_args)) => merge(merge_args),
+        Some(Command::Filter(filter_args)) => filter(filter_args),
+        Some(Command::InstallGitFilter(install_args)) => install_git_filter(install_args),
+        Some(Command::Scrub(scrub_args)) => scrub(scrub_args),
+        Some(Command::ApplyEnv(apply_args)) => apply_env(apply_args),
+        Some(Command::ExtractEnv(extract_args)) => extract_env(extract_args),
+        None => run(&args),
+    };
+
+    match result {
+        Ok(code) => ExitCode::from(code),
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::from(exit_code(&e))
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<u8> {
+    let inputs = inputs(args)?;
+    let to_stdout = args.stdout || matches!(inputs.as_slice(), [Input::Stdin]);
+    if to_stdout && inputs.len() > 1 {
+        Args::conflict("--stdout can only be used with a single file");
+    }
+    let quiet = to_stdout && !args.check;
+
+    let mut code = 0;
+    let mut failures = 0;
+    for input in &inputs {
+        let name = input.name();
+        let outcome = if args.check {
+            check(input, args)
+        } else {
+            sort(input, args, to_stdout)
+        };
+
+        match outcome {
+            Ok(_) if quiet => {}
+            Ok(Outcome::Sorted) => println!("{name}: sorted"),
+            Ok(Outcome::Unchanged) => println!("{name}: already sorted"),
+            Ok(Outcome::Unsorted(report)) => {
+                failures += 1;
+                code = code.max(EXIT_UNSORTED);
+                println!("{name}: not sorted");
+                if report.unsorted.is_empty() {
+                    println!("  formatting differs from sorted output");
+                }
+                for collection in &report.unsorted {
+                    println!("  {collection} is out of order");
+                }
+            }
+            Err(e) => {
+                failures += 1;
+                code = code.max(exit_code(&e));
+                eprintln!("{name}: {e}");
+            }
+        }
+    }
+
+    if inputs.len() > 1 {
+        println!("{} file(s) processed, {failures} failed", inputs.len());
+    }
+    Ok(code)
+}
+
+fn diff(args: &DiffArgs) -> Result<u8> {
+    let old = Bim::from_file_with(&args.old, args.read.options())?;
+    let new = Bim::from_file_with(&args.new, args.read.options())?;
+    let diff = diff::diff(&old, &new)?;
+
+    match args.format {
+        Format::Text => print!("{diff}"),
+        Format::Json => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&diff).map_err(Error::Serialize)?
+            );
+        }
+    }
+    Ok(if diff.is_empty() { 0 } else { EXIT_DIFFERENT })
+}
+
+fn merge(args: &MergeArgs) -> Result<u8> {
+    // git passes an empty base when the file was added on both sides.
+    let base = if fs::metadata(&args.base)?.len() == 0 {
+        None
+    } else {
+        Some(Bim::from_file_with(&args.base, args.read.options())?)
+    };
+    let ours = Bim::from_file_with(&args.ours, args.read.options())?;
+    let theirs = Bim::from_file_with(&args.theirs, args.read.options())?;
+
+    let mut merge = merge::merge(base.as_ref(), &ours, &theirs)?;
+    merge.bim.validate()?;
+    if let Some(style) = args.style {
+        merge.bim.layout.style = style.into();
     }
+    merge.bim.to_file(&args.ours)?;
+
+    if merge.is_clean() {
+        return Ok(0);
+    }
+
+    let mut sidecar = args
+        .path
+        .as_ref()
+        .unwrap_or(&args.ours)
+        .clone()
+        .into_os_string();
+    sidecar.push(".theirs");
+    fs::copy(&args.theirs, &sidecar)?;
+    for conflict in &merge.conflicts {
+        eprintln!("conflict: {conflict} changed on both sides, kept ours");
+    }
+    eprintln!("their version is in {}", PathBuf::from(sidecar).display());
+    Ok(EXIT_CONFLICT)
+}
+
+fn filter(args: &FilterArgs) -> Result<u8> {
+    let input = args.file.clone().map_or(Input::Stdin, Input::File);
+    let contents = input.read_bytes()?;
+
+    let sorted = git::filter(&contents, args.read.options(), args.style.map(Into::into));
+    let output = match &sorted {
+        Ok(sorted) => sorted.as_bytes(),
+        Err(e) => {
+            eprintln!(
+                "warning: {}: {e}, passing it through unsorted",
+                input.name()
+            );
+            &contents
+        }
+    };
+
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(output)?;
+    stdout.flush()?;
+    Ok(0)
+}
+
+fn install_git_filter(args: &InstallArgs) -> Result<u8> {
+    let install = Install {
+        program: String::from("bim_sort"),
+        options: args.options(),
+        merge: args.merge,
+    };
+    let attributes = install.run()?;
+
+    println!("updated {}", attributes.display());
+    for (key, value) in install.config() {
+        println!("set {key} = {value}");
+    }
+    Ok(0)
+}
+
+fn scrub(args: &ScrubArgs) -> Result<u8> {
+    let mut code = 0;
+    for path in bim_files(&args.files)? {
+        let name = path.display();
+        let mut bim = Bim::from_file_with(&path, args.read.options())?;
+
+        if args.check {
+            let secrets = bim.model.secrets();
+            if secrets.is_empty() {
+                println!("{name}: no secrets");
+            } else {
+                code = EXIT_SECRETS;
+                println!("{name}: contains secrets");
+                for secret in &secrets {
+                    println!("  {secret}");
+                }
+            }
+        } else {
+            let before = serde_json::to_value(&bim.model).map_err(Error::Serialize)?;
+            bim.model.scrub(args.policy());
+            if serde_json::to_value(&bim.model).map_err(Error::Serialize)? == before {
+                println!("{name}: nothing to scrub");
+                continue;
+            }
+
+            if let Some(style) = args.style {
+                bim.layout.style = style.into();
+            }
+            bim.to_file_with(&path, &args.write.options())?;
+            println!("{name}: scrubbed");
+        }
+    }
+    Ok(code)
+}
+
+fn apply_env(args: &ApplyEnvArgs) -> Result<u8> {
+    let environment = Environment::from_file(&args.env)?;
+    let mut bim = Bim::from_file_with(&args.file, args.read.options())?;
+
+    environment.apply(&mut bim, args.read.options())?;
+    if let Some(style) = args.style {
+        bim.layout.style = style.into();
+    }
+    bim.to_file(&args.file)?;
+    println!("{}: applied {}", args.file.display(), args.env.display());
+    Ok(0)
+}
+
+fn extract_env(args: &ExtractEnvArgs) -> Result<u8> {
+    let bim = Bim::from_file_with(&args.file, args.read.options())?;
+    let json = Environment::extract(&bim)?.to_json()?;
+
+    match &args.output {
+        Some(path) => fs::write(path, json)?,
+        None => {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(json.as_bytes())?;
+            stdout.flush()?;
+        }
+    }
+    Ok(0)
+}
+
+fn bim_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let files = files::expand(paths)?;
+    if files.is_empty() {
+        Args::no_files(paths);
+    }
+    Ok(files)
+}
+
+fn inputs(args: &Args) -> Result<Vec<Input>> {
+    let is_stdin = |path: &PathBuf| path.as_os_str() == "-";
+
+    if args.stdin || args.files.iter().any(is_stdin) {
+        if args.files.iter().any(|path| !is_stdin(path)) {
+            Args::conflict("stdin cannot be combined with file paths");
+        }
+        return Ok(vec![Input::Stdin]);
+    }
+
+    let paths = bim_files(&args.files)?;
+    Ok(paths.into_iter().map(Input::File).collect())
+}
+
+fn read(input: &Input, args: &Args) -> Result<(Bim, String)> {
+    let contents = input.read()?;
+    let mut bim = Bim::from_str_with(&contents, args.read.options())?;
+    args.apply_layout(&mut bim.layout);
+    Ok((bim, contents))
+}
+
+fn sort(input: &Input, args: &Args, to_stdout: bool) -> Result<Outcome> {
+    let (mut bim, contents) = read(input, args)?;
+    bim.validate()?;
+    bim.sort();
+
+    if to_stdout {
+        bim.to_writer(io::stdout().lock())?;
+        return Ok(Outcome::Sorted);
+    }
+
+    if bim.to_json()? == contents {
+        return Ok(Outcome::Unchanged);
+    }
+    if let Input::File(path) = input {
+        bim.to_file_with(path, &args.write.options())?;
+    }
+    Ok(Outcome::Sorted)
+}
+
+fn check(input: &Input, args: &Args) -> Result<Outcome> {
+    let (bim, contents) = read(input, args)?;
+    let report = check::check(bim, &contents)?;
 
-    if let Some(infile) = args.file {
-        let mut bim = Bim::from_file(&infile)?;
-        bim.sort();
-        bim.to_file(&infile)?;
+    if report.is_sorted() {
+        Ok(Outcome::Unchanged)
+    } else {
+        Ok(Outcome::Unsorted(report))
     }
-    Ok(())
 }
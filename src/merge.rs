@@ -0,0 +1,370 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde_json::{Map, Value};
+
+use crate::check::identity;
+use crate::error::{Error, Result};
+use crate::models::unknown::preserving_unknown;
+use crate::models::{Bim, ParseError};
+
+/// The result of a three-way merge.
+#[derive(Debug)]
+pub struct Merge {
+    /// The merged and sorted bim file. Where both sides changed the same
+    /// object it keeps our version.
+    pub bim: Bim,
+
+    /// Paths of the objects changed on both sides, e.g.
+    /// `model.tables["Sales"].measures["Total"].expression`.
+    pub conflicts: Vec<String>,
+}
+
+impl Merge {
+    #[must_use]
+    pub const fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merges the changes made in `ours` and `theirs` since `base`.
+///
+/// Collections are merged by object name, so objects added, removed or
+/// changed on only one side are taken from that side. A conflict is only
+/// raised when the same property of the same object changed differently on
+/// both sides. Without a `base`, as when a file was added on both sides, the
+/// two versions are merged as if everything in them were added. The result
+/// has the layout of `ours`.
+///
+/// # Errors
+/// Returns an error if a model cannot be represented as JSON or the merged
+/// model is not valid.
+pub fn merge(base: Option<&Bim>, ours: &Bim, theirs: &Bim) -> Result<Merge> {
+    let to_value = |bim: &Bim| serde_json::to_value(bim).map_err(Error::Serialize);
+    let base_value = base.map(to_value).transpose()?;
+
+    let [ours_value, theirs_value] = [to_value(ours)?, to_value(theirs)?];
+
+    let mut conflicts = Vec::new();
+    let merged = match &base_value {
+        Some(base_value) => {
+            merge_values("", base_value, &ours_value, &theirs_value, &mut conflicts)
+        }
+        None => merge_added("", &ours_value, &theirs_value, &mut conflicts),
+    };
+
+    // Unknown properties in the inputs were already accepted when reading.
+    let mut bim: Bim =
+        preserving_unknown(true, || serde_json::from_value(merged)).map_err(ParseError::from)?;
+    bim.layout = ours.layout;
+    bim.sort();
+
+    Ok(Merge { bim, conflicts })
+}
+
+fn merge_values(
+    path: &str,
+    base: &Value,
+    ours: &Value,
+    theirs: &Value,
+    conflicts: &mut Vec<String>,
+) -> Value {
+    if ours == theirs || theirs == base {
+        return ours.clone();
+    }
+    if ours == base {
+        return theirs.clone();
+    }
+
+    match (base, ours, theirs) {
+        (Value::Object(b), Value::Object(o), Value::Object(t)) => {
+            Value::Object(merge_objects(path, b, o, t, conflicts))
+        }
+        (Value::Array(b), Value::Array(o), Value::Array(t)) if all_named(&[b, o, t]) => {
+            Value::Array(merge_named(path, b, o, t, conflicts))
+        }
+        _ => {
+            conflicts.push(display_path(path));
+            ours.clone()
+        }
+    }
+}
+
+/// Merges a value that may be missing on any side, returning `None` if it
+/// should be removed.
+fn merge_optional(
+    path: &str,
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    conflicts: &mut Vec<String>,
+) -> Option<Value> {
+    match (base, ours, theirs) {
+        (Some(b), Some(o), Some(t)) => Some(merge_values(path, b, o, t, conflicts)),
+        (None, Some(o), Some(t)) => Some(merge_added(path, o, t, conflicts)),
+        (Some(b), Some(o), None) => {
+            if o != b {
+                conflicts.push(display_path(path));
+                return Some(o.clone());
+            }
+            None
+        }
+        (Some(b), None, Some(t)) => {
+            if t != b {
+                conflicts.push(display_path(path));
+            }
+            None
+        }
+        (None, Some(v), None) | (None, None, Some(v)) => Some(v.clone()),
+        (_, None, None) => None,
+    }
+}
+
+/// Merges a value added on both sides, as if the base had an empty object or
+/// collection in its place, so only properties that differ conflict.
+fn merge_added(path: &str, ours: &Value, theirs: &Value, conflicts: &mut Vec<String>) -> Value {
+    match (ours, theirs) {
+        (Value::Object(_), Value::Object(_)) => {
+            merge_values(path, &Value::Object(Map::new()), ours, theirs, conflicts)
+        }
+        (Value::Array(_), Value::Array(_)) => {
+            merge_values(path, &Value::Array(Vec::new()), ours, theirs, conflicts)
+        }
+        _ => {
+            if ours != theirs {
+                conflicts.push(display_path(path));
+            }
+            ours.clone()
+        }
+    }
+}
+
+fn merge_objects(
+    path: &str,
+    base: &Map<String, Value>,
+    ours: &Map<String, Value>,
+    theirs: &Map<String, Value>,
+    conflicts: &mut Vec<String>,
+) -> Map<String, Value> {
+    let keys = ours
+        .keys()
+        .chain(theirs.keys().filter(|k| !ours.contains_key(*k)))
+        .chain(
+            base.keys()
+                .filter(|k| !ours.contains_key(*k) && !theirs.contains_key(*k)),
+        );
+
+    let mut merged = Map::new();
+    for key in keys {
+        let child = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{path}.{key}")
+        };
+        if let Some(value) = merge_optional(
+            &child,
+            base.get(key),
+            ours.get(key),
+            theirs.get(key),
+            conflicts,
+        ) {
+            merged.insert(key.clone(), value);
+        }
+    }
+    merged
+}
+
+/// Merges collections of named objects by name, ignoring case as Analysis
+/// Services does, in the order of `ours` followed by objects only in `theirs`.
+fn merge_named(
+    path: &str,
+    base: &[Value],
+    ours: &[Value],
+    theirs: &[Value],
+    conflicts: &mut Vec<String>,
+) -> Vec<Value> {
+    let names = ours.iter().chain(theirs).chain(base).filter_map(identity);
+
+    let mut merged: Vec<Value> = Vec::new();
+    let mut seen = Vec::new();
+    for name in names {
+        let key = name.to_lowercase();
+        if seen.contains(&key) {
+            continue;
+        }
+        seen.push(key);
+
+        let child = format!("{path}[{name:?}]");
+        if let Some(value) = merge_optional(
+            &child,
+            find(base, name),
+            find(ours, name),
+            find(theirs, name),
+            conflicts,
+        ) {
+            merged.push(value);
+        }
+    }
+    merged
+}
+
+fn find<'a>(items: &'a [Value], name: &str) -> Option<&'a Value> {
+    let name = name.to_lowercase();
+    items
+        .iter()
+        .find(|v| identity(v).is_some_and(|n| n.to_lowercase() == name))
+}
+
+fn all_named(collections: &[&Vec<Value>]) -> bool {
+    collections
+        .iter()
+        .all(|items| items.iter().all(|v| identity(v).is_some()))
+}
+
+fn display_path(path: &str) -> String {
+    if path.is_empty() {
+        String::from(".")
+    } else {
+        path.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::models::test::bim_value;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn bim(measures: &Value) -> Bim {
+        let tables =
+            json!([{"name": "Sales", "columns": [], "partitions": [], "measures": measures}]);
+        Bim::from_str(&bim_value(json!({"tables": tables})).to_string()).unwrap()
+    }
+
+    fn measure_names(bim: &Bim) -> Vec<String> {
+        let value = serde_json::to_value(bim).unwrap();
+        value["model"]["tables"][0]["measures"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn measures_added_on_both_sides_are_merged() {
+        let base = bim(&json!([{"name": "Total", "expression": "1"}]));
+        let ours = bim(&json!([
+            {"name": "Total", "expression": "1"},
+            {"name": "Margin", "expression": "2"}
+        ]));
+        let theirs = bim(&json!([
+            {"name": "Total", "expression": "1"},
+            {"name": "Count", "expression": "3"}
+        ]));
+
+        let merge = merge(Some(&base), &ours, &theirs).unwrap();
+
+        assert!(merge.is_clean());
+        assert_eq!(measure_names(&merge.bim), vec!["Count", "Margin", "Total"]);
+    }
+
+    #[test]
+    fn removal_and_change_on_different_objects_merge() {
+        let base = bim(&json!([
+            {"name": "Total", "expression": "1"},
+            {"name": "Margin", "expression": "2"}
+        ]));
+        let ours = bim(&json!([{"name": "Total", "expression": "1"}]));
+        let theirs = bim(&json!([
+            {"name": "Total", "expression": "10"},
+            {"name": "Margin", "expression": "2"}
+        ]));
+
+        let merge = merge(Some(&base), &ours, &theirs).unwrap();
+
+        assert!(merge.is_clean());
+        assert_eq!(
+            merge.bim,
+            bim(&json!([{"name": "Total", "expression": "10"}]))
+        );
+    }
+
+    #[test]
+    fn same_object_changed_on_both_sides_conflicts() {
+        let base = bim(&json!([{"name": "Total", "expression": "1"}]));
+        let ours = bim(&json!([{"name": "Total", "expression": "2"}]));
+        let theirs = bim(&json!([{"name": "Total", "expression": "3"}]));
+
+        let merge = merge(Some(&base), &ours, &theirs).unwrap();
+
+        assert_eq!(
+            merge.conflicts,
+            vec!["model.tables[\"Sales\"].measures[\"Total\"].expression"]
+        );
+        assert_eq!(merge.bim, ours);
+    }
+
+    #[test]
+    fn without_a_base_objects_added_on_both_sides_merge() {
+        let ours = bim(&json!([
+            {"name": "Total", "expression": "1"},
+            {"name": "Count", "expression": "2"}
+        ]));
+        let theirs = bim(&json!([
+            {"name": "Total", "expression": "1"},
+            {"name": "Average", "expression": "3"}
+        ]));
+
+        let merge = merge(None, &ours, &theirs).unwrap();
+
+        assert!(merge.is_clean());
+        assert_eq!(measure_names(&merge.bim), vec!["Average", "Count", "Total"]);
+    }
+
+    #[test]
+    fn without_a_base_differing_properties_conflict() {
+        let ours = bim(&json!([{"name": "Total", "expression": "1"}]));
+        let theirs = bim(&json!([{"name": "Total", "expression": "2"}]));
+
+        let merge = merge(None, &ours, &theirs).unwrap();
+
+        assert_eq!(
+            merge.conflicts,
+            vec!["model.tables[\"Sales\"].measures[\"Total\"].expression"]
+        );
+        assert_eq!(merge.bim, ours);
+    }
+
+    #[test]
+    fn objects_renamed_by_case_are_matched() {
+        let base = bim(&json!([{"name": "Total", "expression": "1"}]));
+        let ours = bim(&json!([{"name": "TOTAL", "expression": "1"}]));
+        let theirs = bim(&json!([
+            {"name": "Total", "expression": "2"},
+            {"name": "Count", "expression": "3"}
+        ]));
+
+        let merge = merge(Some(&base), &ours, &theirs).unwrap();
+
+        assert!(merge.is_clean());
+        assert_eq!(measure_names(&merge.bim), vec!["Count", "TOTAL"]);
+        assert!(merge.bim.validate().is_ok());
+    }
+}
@@ -16,14 +16,18 @@ You should have received a copy of the GNU General Public License
 along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use crate::models::unknown::UnknownFields;
 use crate::models::Expression;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct Annotation {
     pub name: String,
     pub value: Expression,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
 }
 
 impl Ord for Annotation {
@@ -16,55 +16,432 @@ You should have received a copy of the GNU General Public License
 along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-pub use io::Result;
 use std::fs;
-use std::io;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
-use super::Model;
+use super::format;
+use super::layout::Layout;
+use super::unknown::{preserving_unknown, UnknownFields};
+use super::{Model, ParseError};
+use crate::error::{Error, Result};
 
 /// The root bim file.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct Bim {
     pub name: String,
     pub compatibility_level: u32,
     pub model: Model,
     pub id: String,
+
+    /// The BOM, line endings and formatting style of the file, reproduced
+    /// when writing.
+    #[serde(skip)]
+    pub layout: Layout,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+/// Options controlling how a bim file is read.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReadOptions {
+    /// Keep properties that are not modelled and write them back unchanged,
+    /// instead of rejecting the file.
+    pub preserve_unknown: bool,
+}
+
+/// Options controlling how a bim file is written to disk.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Keep a copy of the original file, named by appending this suffix.
+    pub backup: Option<String>,
 }
 
 impl Bim {
-    pub fn from_file(path: &PathBuf) -> io::Result<Self> {
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not a valid bim file.
+    pub fn from_file(path: &PathBuf) -> Result<Self> {
+        Self::from_file_with(path, ReadOptions::default())
+    }
+
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not a valid bim file.
+    pub fn from_file_with(path: &PathBuf, options: ReadOptions) -> Result<Self> {
         let data = fs::read_to_string(path)?;
-        let res = Self::from_str(&data)?;
+        let res = Self::from_str_with(&data, options)?;
         Ok(res)
     }
 
-    pub fn to_file(&self, path: &PathBuf) -> io::Result<()> {
-        let contents = self.to_string();
-        fs::write(path, contents)?;
+    /// # Errors
+    /// Returns an error if reading fails or the input is not a valid bim file.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        Self::from_reader_with(reader, ReadOptions::default())
+    }
+
+    /// # Errors
+    /// Returns an error if reading fails or the input is not a valid bim file.
+    pub fn from_reader_with<R: Read>(mut reader: R, options: ReadOptions) -> Result<Self> {
+        let mut data = String::new();
+        reader.read_to_string(&mut data)?;
+        let res = Self::from_str_with(&data, options)?;
+        Ok(res)
+    }
+
+    /// # Errors
+    /// Returns [`Error::Parse`] if `s` is not a valid bim file.
+    pub fn from_str_with(s: &str, options: ReadOptions) -> Result<Self> {
+        let layout = Layout::detect(s);
+        let s = Layout::strip_bom(s);
+
+        let mut deserializer = serde_json::Deserializer::from_str(s);
+        let mut bim: Self = preserving_unknown(options.preserve_unknown, || {
+            serde_path_to_error::deserialize(&mut deserializer)
+        })
+        .map_err(|e| ParseError::new(e, s))?;
+        deserializer.end().map_err(ParseError::from)?;
+
+        bim.layout = layout;
+        Ok(bim)
+    }
+
+    /// Serializes the bim file as JSON, in its [`Layout`] and style.
+    ///
+    /// # Errors
+    /// Returns [`Error::Serialize`] if the model cannot be represented as JSON.
+    pub fn to_json(&self) -> Result<String> {
+        let json = format::to_string(self, self.layout.style).map_err(Error::Serialize)?;
+        Ok(self.layout.apply(&json))
+    }
+
+    /// # Errors
+    /// Returns an error if serialization or writing fails. Nothing is written
+    /// if serialization fails.
+    pub fn to_file(&self, path: &PathBuf) -> Result<()> {
+        self.to_file_with(path, &WriteOptions::default())
+    }
+
+    /// Writes to a temporary file in the same directory and renames it over
+    /// `path`, so the original is never left partially written. The original
+    /// file's permissions are kept.
+    ///
+    /// # Errors
+    /// Returns an error if serialization, the backup or writing fails. The
+    /// original file is untouched if any step fails.
+    pub fn to_file_with(&self, path: &PathBuf, options: &WriteOptions) -> Result<()> {
+        let contents = self.to_json()?;
+
+        if let Some(suffix) = &options.backup {
+            if path.exists() {
+                let mut backup = path.clone().into_os_string();
+                backup.push(suffix);
+                fs::copy(path, backup)?;
+            }
+        }
+
+        write_atomic(path, contents.as_bytes())?;
+        Ok(())
+    }
+
+    /// # Errors
+    /// Returns an error if serialization or writing fails. Nothing is written
+    /// if serialization fails.
+    pub fn to_writer<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(self.to_json()?.as_bytes())?;
+        writer.flush()?;
         Ok(())
     }
 
+    /// Checks the model is internally consistent.
+    ///
+    /// # Errors
+    /// Returns [`Error::Validation`] listing every problem found.
+    pub fn validate(&self) -> Result<()> {
+        let problems = self.model.validate();
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Validation(problems))
+        }
+    }
+
     pub fn sort(&mut self) {
         use super::traits::RecursiveSort;
         self.model.recursive_sort();
     }
 }
 
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.tmp", std::process::id()));
+    let temp = dir.join(name);
+
+    let write = || -> std::io::Result<()> {
+        let mut file = fs::File::create(&temp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&temp, metadata.permissions())?;
+        }
+        fs::rename(&temp, path)
+    };
+
+    write().inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
 impl FromStr for Bim {
-    type Err = serde_json::Error;
+    type Err = Error;
 
-    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        serde_json::from_str(s)
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_str_with(s, ReadOptions::default())
     }
 }
 
-impl ToString for Bim {
-    fn to_string(&self) -> String {
-        serde_json::to_string_pretty(self).unwrap_or_else(|_| String::from("null"))
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use serde_json::json;
+
+    use super::{Bim, ReadOptions, WriteOptions};
+    use crate::models::scrub::ScrubPolicy;
+    use crate::models::test::{bim_value, TempDir};
+    use crate::Error;
+    use std::str::FromStr;
+
+    const SAMPLE: &str = include_str!("../../tests/sample.bim");
+
+    #[test]
+    fn reader_and_writer_round_trip() {
+        let bim = Bim::from_reader(SAMPLE.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        bim.to_writer(&mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output, bim.to_json().unwrap());
+        assert_eq!(Bim::from_reader(output.as_bytes()).unwrap(), bim);
+    }
+
+    #[test]
+    fn unknown_fields_are_preserved_on_request() {
+        let input = include_str!("../../tests/bim_with_root_error.bim");
+        let options = ReadOptions {
+            preserve_unknown: true,
+        };
+
+        assert!(Bim::from_str(input).is_err());
+        let bim = Bim::from_str_with(input, options).unwrap();
+
+        let output: serde_json::Value = serde_json::from_str(&bim.to_json().unwrap()).unwrap();
+        assert_eq!(output["UNKNOWN_FIELD"], "I AM UNKNOWN");
+        assert_eq!(
+            Bim::from_str_with(&bim.to_json().unwrap(), options).unwrap(),
+            bim
+        );
+    }
+
+    #[test]
+    fn relationships_to_missing_columns_fail_validation() {
+        let mut bim = sample();
+        assert!(bim.validate().is_ok());
+
+        bim.model.relationships[0].to_column = String::from("missing");
+
+        match bim.validate() {
+            Err(Error::Validation(problems)) => {
+                assert_eq!(problems.len(), 1);
+                assert!(problems[0].contains("unknown column"));
+            }
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn relationships_match_names_case_insensitively() {
+        let mut bim = sample();
+        let relationship = &mut bim.model.relationships[0];
+        relationship.from_table = relationship.from_table.to_lowercase();
+        relationship.to_column = relationship.to_column.to_uppercase();
+
+        assert!(bim.validate().is_ok());
+    }
+
+    #[test]
+    fn scrubbing_addresses_redacts_them_from_names_and_m() {
+        let input = bim_value(json!({
+            "dataSources": [
+                {
+                    "type": "structured",
+                    "name": "SQL/prod-sql;Sales",
+                    "connectionDetails": {
+                        "protocol": "tds",
+                        "address": {"server": "prod-sql", "database": "Sales"},
+                        "authentication": null,
+                        "query": null
+                    },
+                    "credential": {
+                        "AuthenticationKind": "Windows",
+                        "kind": "SQL",
+                        "path": "prod-sql;Sales"
+                    }
+                }
+            ],
+            "tables": [
+                {
+                    "name": "Orders",
+                    "columns": [],
+                    "partitions": [
+                        {
+                            "name": "Orders",
+                            "source": {
+                                "type": "m",
+                                "expression": [
+                                    "let",
+                                    "    Source = #\"SQL/prod-sql;Sales\",",
+                                    "    Direct = Sql.Database(\"prod-sql\", \"Sales\")",
+                                    "in",
+                                    "    Source{[Schema=\"dbo\",Item=\"Orders\"]}[Data]"
+                                ]
+                            }
+                        }
+                    ]
+                }
+            ],
+            "expressions": [
+                {
+                    "name": "Server",
+                    "kind": "m",
+                    "expression": "\"prod-sql\" meta [IsParameterQuery=true, Type=\"Text\"]"
+                }
+            ]
+        }));
+        let mut bim = Bim::from_str(&input.to_string()).unwrap();
+
+        bim.model.scrub(ScrubPolicy {
+            usernames: false,
+            addresses: true,
+        });
+
+        let output = bim.to_json().unwrap();
+        assert!(!output.contains("prod-sql"), "{output}");
+        assert!(!output.contains("\\\"Sales\\\""), "{output}");
+        let output: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let model = &output["model"];
+        assert_eq!(model["dataSources"][0]["name"], "SQL/<redacted>");
+        assert_eq!(
+            model["tables"][0]["partitions"][0]["source"]["expression"],
+            json!([
+                "let",
+                "    Source = #\"SQL/<redacted>\",",
+                "    Direct = Sql.Database(\"<redacted>\", \"<redacted>\")",
+                "in",
+                "    Source{[Schema=\"dbo\",Item=\"Orders\"]}[Data]"
+            ])
+        );
+        assert_eq!(
+            model["expressions"][0]["expression"],
+            "\"<redacted>\" meta [IsParameterQuery=true, Type=\"Text\"]"
+        );
+        assert!(bim.validate().is_ok());
+    }
+
+    #[test]
+    fn scrubbed_data_source_names_stay_unique() {
+        let data_source = |server: &str| {
+            json!({
+                "type": "structured",
+                "name": format!("SQL/{server};Sales"),
+                "connectionDetails": {
+                    "protocol": "tds",
+                    "address": {"server": server, "database": "Sales"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "Windows",
+                    "kind": "SQL",
+                    "path": format!("{server};Sales")
+                }
+            })
+        };
+        let input = bim_value(json!({
+            "dataSources": [data_source("prod-sql"), data_source("test-sql")]
+        }));
+        let mut bim = Bim::from_str(&input.to_string()).unwrap();
+
+        bim.model.scrub(ScrubPolicy {
+            usernames: false,
+            addresses: true,
+        });
+
+        let names: Vec<&str> = bim.model.data_sources.iter().map(|d| d.name()).collect();
+        assert_eq!(names, ["SQL/<redacted>", "SQL/<redacted> 2"]);
+    }
+
+    fn sample() -> Bim {
+        Bim::from_str(SAMPLE).unwrap()
+    }
+
+    #[test]
+    fn to_file_replaces_contents_without_leaving_temporary_files() {
+        let dir = TempDir::new("atomic");
+        let path = dir.touch("Model.bim");
+        let bim = sample();
+
+        bim.to_file(&path).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), bim.to_json().unwrap());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn to_file_with_backup_keeps_the_original() {
+        let dir = TempDir::new("backup");
+        let path = dir.touch("Model.bim");
+        fs::write(&path, "original").unwrap();
+        let options = WriteOptions {
+            backup: Some(String::from(".orig")),
+        };
+
+        sample().to_file_with(&path, &options).unwrap();
+
+        let backup = dir.path().join("Model.bim.orig");
+        assert_eq!(fs::read_to_string(backup).unwrap(), "original");
+        assert_ne!(fs::read_to_string(&path).unwrap(), "original");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn to_file_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new("permissions");
+        let path = dir.touch("Model.bim");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        sample().to_file(&path).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+
+    #[test]
+    fn layout_is_reproduced_when_writing() {
+        let sorted = sample().to_json().unwrap();
+        let input = format!("\u{feff}{}\r\n", sorted.replace('\n', "\r\n"));
+
+        let bim = Bim::from_str(&input).unwrap();
+
+        assert_eq!(bim.to_json().unwrap(), input);
     }
 }
@@ -0,0 +1,359 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::models::annotations::Annotation;
+use crate::models::traits::RecursiveSort;
+use crate::models::unknown::UnknownFields;
+
+/// The translations and Q&A metadata of the model for one language.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Culture {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linguistic_metadata: Option<LinguisticMetadata>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translations: Option<Translations>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Vec<Annotation>>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl RecursiveSort for Culture {
+    fn recursive_sort(&mut self) {
+        if let Some(t) = &mut self.translations {
+            t.model.recursive_sort();
+        }
+        if let Some(a) = &mut self.annotations {
+            a.sort();
+        }
+    }
+}
+
+impl Ord for Culture {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name.to_lowercase().cmp(&other.name.to_lowercase())
+    }
+}
+
+impl PartialOrd for Culture {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The synonyms and phrasings used by Q&A. The content is written back
+/// exactly as read, neither modelled nor sorted.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LinguisticMetadata {
+    pub content: Value,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Translations {
+    pub model: ModelTranslation,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelTranslation {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translated_caption: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translated_description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tables: Option<Vec<TableTranslation>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub perspectives: Option<Vec<Translation>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roles: Option<Vec<Translation>>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl RecursiveSort for ModelTranslation {
+    fn recursive_sort(&mut self) {
+        if let Some(t) = &mut self.tables {
+            t.recursive_sort();
+        }
+        if let Some(p) = &mut self.perspectives {
+            p.sort();
+        }
+        if let Some(r) = &mut self.roles {
+            r.sort();
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TableTranslation {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translated_caption: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translated_description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<Translation>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub measures: Option<Vec<Translation>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hierarchies: Option<Vec<HierarchyTranslation>>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl RecursiveSort for TableTranslation {
+    fn recursive_sort(&mut self) {
+        if let Some(c) = &mut self.columns {
+            c.sort();
+        }
+        if let Some(m) = &mut self.measures {
+            m.sort();
+        }
+        if let Some(h) = &mut self.hierarchies {
+            h.recursive_sort();
+        }
+    }
+}
+
+impl Ord for TableTranslation {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name.to_lowercase().cmp(&other.name.to_lowercase())
+    }
+}
+
+impl PartialOrd for TableTranslation {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HierarchyTranslation {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translated_caption: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translated_description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translated_display_folder: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub levels: Option<Vec<Translation>>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl RecursiveSort for HierarchyTranslation {
+    fn recursive_sort(&mut self) {
+        if let Some(l) = &mut self.levels {
+            l.sort();
+        }
+    }
+}
+
+impl Ord for HierarchyTranslation {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name.to_lowercase().cmp(&other.name.to_lowercase())
+    }
+}
+
+impl PartialOrd for HierarchyTranslation {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The translation of a column, measure, level, perspective or role.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Translation {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translated_caption: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translated_description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translated_display_folder: Option<String>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl Ord for Translation {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name.to_lowercase().cmp(&other.name.to_lowercase())
+    }
+}
+
+impl PartialOrd for Translation {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::models::test::{there_and_back_test, FromValue};
+
+    #[test]
+    fn can_read_and_write_culture() {
+        let input = json!(
+            {
+                "name": "fr-FR",
+                "linguisticMetadata": {
+                    "content": {
+                        "Version": "1.0.0",
+                        "Language": "fr-FR",
+                        "Entities": {"ventes": {"Binding": {"ConceptualEntity": "Sales"}}}
+                    },
+                    "contentType": "json"
+                },
+                "translations": {
+                    "model": {
+                        "name": "Model",
+                        "translatedCaption": "Modèle",
+                        "tables": [
+                            {
+                                "name": "Sales",
+                                "translatedCaption": "Ventes",
+                                "columns": [
+                                    {
+                                        "name": "Amount",
+                                        "translatedCaption": "Montant",
+                                        "translatedDisplayFolder": "Valeurs"
+                                    }
+                                ],
+                                "measures": [{"name": "Total", "translatedCaption": "Total"}],
+                                "hierarchies": [
+                                    {
+                                        "name": "Products",
+                                        "translatedCaption": "Produits",
+                                        "levels": [{"name": "Category", "translatedCaption": "Catégorie"}]
+                                    }
+                                ]
+                            }
+                        ],
+                        "perspectives": [{"name": "Sales", "translatedCaption": "Ventes"}],
+                        "roles": [{"name": "Readers", "translatedDescription": "Lecteurs"}]
+                    }
+                }
+            }
+        );
+
+        there_and_back_test(&input, Culture::from_value);
+    }
+
+    #[test]
+    fn linguistic_metadata_content_is_preserved_unsorted() {
+        let input = r#"{"name":"en-US","linguisticMetadata":{"content":{"Version":"1.0.0","Language":"en-US","DynamicImprovement":"HighConfidence"},"contentType":"json"}}"#;
+
+        let mut culture: Culture = serde_json::from_str(input).unwrap();
+        culture.recursive_sort();
+
+        assert_eq!(serde_json::to_string(&culture).unwrap(), input);
+    }
+
+    #[test]
+    fn translations_are_sorted_by_name() {
+        let mut culture = Culture::from_value(&json!(
+            {
+                "name": "de-DE",
+                "translations": {
+                    "model": {
+                        "name": "Model",
+                        "tables": [
+                            {
+                                "name": "sales",
+                                "columns": [{"name": "Quantity"}, {"name": "amount"}],
+                                "measures": [{"name": "Total"}, {"name": "Average"}]
+                            },
+                            {"name": "Calendar", "translatedCaption": "Kalender"}
+                        ]
+                    }
+                }
+            }
+        ));
+        let expected = json!(
+            {
+                "name": "de-DE",
+                "translations": {
+                    "model": {
+                        "name": "Model",
+                        "tables": [
+                            {"name": "Calendar", "translatedCaption": "Kalender"},
+                            {
+                                "name": "sales",
+                                "columns": [{"name": "amount"}, {"name": "Quantity"}],
+                                "measures": [{"name": "Average"}, {"name": "Total"}]
+                            }
+                        ]
+                    }
+                }
+            }
+        );
+
+        culture.recursive_sort();
+
+        assert_eq!(serde_json::to_value(&culture).unwrap(), expected);
+    }
+}
@@ -17,20 +17,90 @@ along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
+use super::scrub::{self, ScrubPolicy, REDACTED};
+use super::unknown::UnknownFields;
+
+/// A connection to external data, tagged by its `type`.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(deny_unknown_fields)]
-pub struct DataSource {
-    #[serde(rename = "type")]
-    pub type_: String,
-    pub name: String,
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DataSource {
+    /// A Power Query source, used from compatibility level 1400.
+    Structured(StructuredDataSource),
 
-    #[serde(rename = "connectionDetails")]
-    pub connection_details: ConnectionDetails,
+    /// A legacy source using a connection string, as in compatibility level
+    /// 1200 models.
+    Provider(ProviderDataSource),
+}
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub options: Option<DataSourceOption>,
-    pub credential: CredentialType,
+impl DataSource {
+    #[must_use]
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Structured(s) => &s.name,
+            Self::Provider(p) => &p.name,
+        }
+    }
+
+    pub(crate) fn rename(&mut self, name: String) {
+        match self {
+            Self::Structured(s) => s.name = name,
+            Self::Provider(p) => p.name = name,
+        }
+    }
+
+    /// Scrubs the data source as `policy` asks, returning the addresses that
+    /// were redacted so they can be found elsewhere in the model.
+    pub(crate) fn scrub(&mut self, policy: ScrubPolicy) -> Vec<String> {
+        let mut redacted = Vec::new();
+        match self {
+            Self::Structured(s) => {
+                if policy.addresses {
+                    redacted.push(s.credential.path());
+                }
+                s.credential.scrub(policy);
+                s.connection_details.scrub(policy, &mut redacted);
+            }
+            Self::Provider(p) => {
+                p.password = None;
+                p.connection_string =
+                    scrub::scrub_connection_string(&p.connection_string, policy, &mut redacted);
+                if policy.usernames && p.account.is_some() {
+                    p.account = Some(REDACTED.to_string());
+                }
+            }
+        }
+        redacted
+    }
+
+    /// Describes each secret held in plain text, e.g. `credential Password`.
+    pub(crate) fn secrets(&self) -> Vec<String> {
+        match self {
+            Self::Structured(s) => s
+                .credential
+                .secrets()
+                .into_iter()
+                .map(|name| format!("credential {name}"))
+                .chain(
+                    s.connection_details
+                        .secrets()
+                        .into_iter()
+                        .map(|name| format!("connectionDetails {name}")),
+                )
+                .collect(),
+            Self::Provider(p) => p
+                .password
+                .iter()
+                .map(|_| String::from("password"))
+                .chain(
+                    scrub::connection_string_secrets(&p.connection_string)
+                        .into_iter()
+                        .map(|name| format!("connectionString {name}")),
+                )
+                .collect(),
+        }
+    }
 }
 
 impl PartialOrd for DataSource {
@@ -41,15 +111,75 @@ impl PartialOrd for DataSource {
 
 impl Ord for DataSource {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.name.to_lowercase().cmp(&other.name.to_lowercase())
+        self.name().to_lowercase().cmp(&other.name().to_lowercase())
     }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(tag = "protocol", deny_unknown_fields)]
+pub struct StructuredDataSource {
+    pub name: String,
+
+    #[serde(rename = "connectionDetails")]
+    pub connection_details: ConnectionDetails,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<DataSourceOption>,
+    pub credential: CredentialType,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderDataSource {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    pub connection_string: String,
+
+    /// How the server connects, e.g. `impersonateServiceAccount` or
+    /// `impersonateAccount`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impersonation_mode: Option<String>,
+
+    /// The account impersonated when `impersonation_mode` is
+    /// `impersonateAccount`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+
+    /// The query timeout in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isolation: Option<String>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(tag = "protocol")]
 pub enum ConnectionDetails {
     #[serde(rename = "document-db")]
-    DocumentDb { address: Address },
+    DocumentDb {
+        address: Address,
+
+        #[serde(flatten)]
+        unknown: UnknownFields,
+    },
 
     #[serde(rename = "tds")]
     Tds(SqlConnection),
@@ -59,18 +189,245 @@ pub enum ConnectionDetails {
 
     #[serde(rename = "mysql")]
     MySql(SqlConnection),
+
+    #[serde(rename = "oracle")]
+    Oracle(Connection<ServerAddress>),
+
+    #[serde(rename = "odbc")]
+    Odbc(Connection<OptionsAddress>),
+
+    #[serde(rename = "oledb")]
+    OleDb(Connection<ConnectionStringAddress>),
+
+    #[serde(rename = "analysis-services")]
+    AnalysisServices(Connection<ServerAddress>),
+
+    #[serde(rename = "azure-blobs")]
+    AzureBlobs(Connection<AzureStorageAddress>),
+
+    #[serde(rename = "azure-data-lake-storage")]
+    AzureDataLakeStorage(Connection<UrlAddress>),
+
+    #[serde(rename = "file")]
+    File(Connection<PathAddress>),
+
+    #[serde(rename = "folder")]
+    Folder(Connection<PathAddress>),
+
+    #[serde(rename = "odata")]
+    OData(Connection<UrlAddress>),
+
+    #[serde(rename = "sharepoint")]
+    SharePoint(Connection<UrlAddress>),
+
+    #[serde(rename = "web")]
+    Web(Connection<UrlAddress>),
+
+    #[serde(rename = "snowflake")]
+    Snowflake(Connection<SnowflakeAddress>),
+
+    #[serde(rename = "databricks")]
+    Databricks(Connection<DatabricksAddress>),
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(deny_unknown_fields)]
 pub struct SqlConnection {
     address: Address,
     authentication: Option<String>,
     query: Option<String>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl ConnectionDetails {
+    /// Drops secrets from ODBC options and OLE DB connection strings, and
+    /// redacts usernames and the address if `policy` asks, adding the
+    /// redacted addresses to `redacted`.
+    fn scrub(&mut self, policy: ScrubPolicy, redacted: &mut Vec<String>) {
+        match self {
+            Self::Odbc(c) => {
+                let options = &mut c.address.options;
+                options.retain(|key, _| !scrub::is_secret(key));
+                if policy.usernames {
+                    options
+                        .iter_mut()
+                        .filter(|(key, _)| scrub::is_username(key))
+                        .for_each(|(_, value)| *value = Value::from(REDACTED));
+                }
+            }
+            Self::OleDb(c) => {
+                c.address.connection_string =
+                    scrub::scrub_connection_string(&c.address.connection_string, policy, redacted);
+            }
+            _ => {}
+        }
+        if policy.addresses {
+            self.scrub_address(redacted);
+        }
+    }
+
+    fn secrets(&self) -> Vec<String> {
+        match self {
+            Self::Odbc(c) => c
+                .address
+                .options
+                .keys()
+                .filter(|key| scrub::is_secret(key))
+                .cloned()
+                .collect(),
+            Self::OleDb(c) => scrub::connection_string_secrets(&c.address.connection_string),
+            _ => Vec::new(),
+        }
+    }
+
+    fn scrub_address(&mut self, redacted: &mut Vec<String>) {
+        match self {
+            Self::DocumentDb { address, .. } => address.scrub(redacted),
+            Self::Tds(c) | Self::PostgresSql(c) | Self::MySql(c) => c.address.scrub(redacted),
+            Self::Oracle(c) | Self::AnalysisServices(c) => {
+                redact_address(&mut c.address.server, redacted);
+                redact_optional_address(&mut c.address.database, redacted);
+            }
+            Self::Odbc(c) => {
+                for value in c.address.options.values_mut() {
+                    if let Value::String(address) = value {
+                        redacted.push(address.clone());
+                    }
+                    *value = Value::from(REDACTED);
+                }
+            }
+            // Already scrubbed with the rest of the connection string.
+            Self::OleDb(_) => {}
+            Self::AzureBlobs(c) => redact_address(&mut c.address.account, redacted),
+            Self::AzureDataLakeStorage(c) | Self::OData(c) | Self::SharePoint(c) | Self::Web(c) => {
+                redact_address(&mut c.address.url, redacted);
+            }
+            Self::File(c) | Self::Folder(c) => redact_address(&mut c.address.path, redacted),
+            Self::Snowflake(c) => {
+                redact_address(&mut c.address.server, redacted);
+                redact_address(&mut c.address.warehouse, redacted);
+                redact_optional_address(&mut c.address.database, redacted);
+            }
+            Self::Databricks(c) => {
+                redact_address(&mut c.address.host, redacted);
+                redact_address(&mut c.address.http_path, redacted);
+            }
+        }
+    }
+}
+
+fn redact(value: &mut Option<String>) {
+    if value.is_some() {
+        *value = Some(REDACTED.to_string());
+    }
+}
+
+/// Replaces an address with [`REDACTED`], adding what it was to `redacted`.
+fn redact_address(address: &mut String, redacted: &mut Vec<String>) {
+    redacted.push(std::mem::replace(address, REDACTED.to_string()));
+}
+
+fn redact_optional_address(address: &mut Option<String>, redacted: &mut Vec<String>) {
+    if let Some(address) = address {
+        redact_address(address, redacted);
+    }
+}
+
+/// The connection details of a protocol whose address has the shape `A`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Connection<A> {
+    pub address: A,
+    pub authentication: Option<String>,
+    pub query: Option<String>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+/// The address of an Oracle or Analysis Services server.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct ServerAddress {
+    pub server: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database: Option<String>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+/// The address of an ODBC source, as the key value pairs of its connection
+/// string, e.g. `{"dsn": "Warehouse"}`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct OptionsAddress {
+    pub options: Map<String, Value>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionStringAddress {
+    pub connection_string: String,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+/// The address of an Azure storage account, e.g. `mystorage` in the domain
+/// `blob.core.windows.net`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct AzureStorageAddress {
+    pub account: String,
+    pub domain: String,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct UrlAddress {
+    pub url: String,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+/// The path of a file or folder, local or on a network share.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct PathAddress {
+    pub path: String,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct SnowflakeAddress {
+    pub server: String,
+    pub warehouse: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database: Option<String>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabricksAddress {
+    pub host: String,
+    pub http_path: String,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(untagged, deny_unknown_fields)]
+#[serde(untagged)]
 pub enum Address {
     DocumentDb {
         url: String,
@@ -80,27 +437,55 @@ pub enum Address {
 
         #[serde(skip_serializing_if = "Option::is_none")]
         collection: Option<String>,
+
+        #[serde(flatten)]
+        unknown: UnknownFields,
     },
     SqlDatabase {
         server: String,
         database: String,
+
+        #[serde(flatten)]
+        unknown: UnknownFields,
     },
 }
 
+impl Address {
+    fn scrub(&mut self, redacted: &mut Vec<String>) {
+        match self {
+            Self::DocumentDb { url, database, .. } => {
+                redact_address(url, redacted);
+                redact_optional_address(database, redacted);
+            }
+            Self::SqlDatabase {
+                server, database, ..
+            } => {
+                redact_address(server, redacted);
+                redact_address(database, redacted);
+            }
+        }
+    }
+}
+
 pub trait Credential {
     fn kind(&self) -> String;
     fn path(&self) -> String;
     fn authentication(&self) -> Authentication;
+
+    /// The account the credential signs in as, for the kinds that have one.
+    fn username(&self) -> Option<String>;
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(deny_unknown_fields)]
 pub struct CredentialCommon {
     kind: String,
     path: String,
 
     #[serde(skip_serializing_if = "Option::is_none", rename = "PrivacySetting")]
     privacy_setting: Option<PrivacySetting>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -112,11 +497,14 @@ pub enum PrivacySetting {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(tag = "AuthenticationKind", deny_unknown_fields)]
+#[serde(tag = "AuthenticationKind")]
 pub enum CredentialType {
     Key {
         #[serde(flatten)]
         common: CredentialCommon,
+
+        #[serde(skip_serializing_if = "Option::is_none", rename = "Key")]
+        key: Option<String>,
     },
     UsernamePassword {
         #[serde(flatten)]
@@ -124,46 +512,240 @@ pub enum CredentialType {
         #[serde(rename = "Username")]
         username: String,
 
+        #[serde(skip_serializing_if = "Option::is_none", rename = "Password")]
+        password: Option<String>,
+
         #[serde(rename = "EncryptConnection")]
         encrypt_connection: bool,
     },
+    Windows {
+        #[serde(flatten)]
+        common: CredentialCommon,
+
+        #[serde(skip_serializing_if = "Option::is_none", rename = "Username")]
+        username: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none", rename = "Password")]
+        password: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none", rename = "EncryptConnection")]
+        encrypt_connection: Option<bool>,
+    },
+    Anonymous {
+        #[serde(flatten)]
+        common: CredentialCommon,
+
+        #[serde(skip_serializing_if = "Option::is_none", rename = "EncryptConnection")]
+        encrypt_connection: Option<bool>,
+    },
+    OAuth2 {
+        #[serde(flatten)]
+        common: CredentialCommon,
+
+        /// When the token stored with the credential expires.
+        #[serde(skip_serializing_if = "Option::is_none", rename = "Expires")]
+        expires: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none", rename = "ProviderType")]
+        provider_type: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none", rename = "AccessToken")]
+        access_token: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none", rename = "RefreshToken")]
+        refresh_token: Option<String>,
+    },
+    /// Connects as the account the Analysis Services service runs under.
+    ServiceAccount {
+        #[serde(flatten)]
+        common: CredentialCommon,
+    },
+    ServicePrincipal {
+        #[serde(flatten)]
+        common: CredentialCommon,
+
+        #[serde(rename = "TenantId")]
+        tenant_id: String,
+
+        #[serde(rename = "ServicePrincipalClientId")]
+        client_id: String,
+    },
+    /// Connects as a Windows account other than the service account.
+    ImpersonateAccount {
+        #[serde(flatten)]
+        common: CredentialCommon,
+        #[serde(rename = "Username")]
+        username: String,
+
+        #[serde(skip_serializing_if = "Option::is_none", rename = "Password")]
+        password: Option<String>,
+    },
 }
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum Authentication {
     Key,
     UsernamePassword,
+    Windows,
+    Anonymous,
+    OAuth2,
+    ServiceAccount,
+    ServicePrincipal,
+    ImpersonateAccount,
 }
 
-impl Credential for CredentialType {
-    #[must_use]
-    fn kind(&self) -> String {
+impl CredentialType {
+    const fn common(&self) -> &CredentialCommon {
         match self {
-            Self::Key { common } | Self::UsernamePassword { common, .. } => common.kind.clone(),
+            Self::Key { common, .. }
+            | Self::UsernamePassword { common, .. }
+            | Self::Windows { common, .. }
+            | Self::Anonymous { common, .. }
+            | Self::OAuth2 { common, .. }
+            | Self::ServiceAccount { common }
+            | Self::ServicePrincipal { common, .. }
+            | Self::ImpersonateAccount { common, .. } => common,
         }
     }
 
-    #[must_use]
-    fn path(&self) -> String {
+    fn common_mut(&mut self) -> &mut CredentialCommon {
         match self {
-            Self::Key { common } | Self::UsernamePassword { common, .. } => common.path.clone(),
+            Self::Key { common, .. }
+            | Self::UsernamePassword { common, .. }
+            | Self::Windows { common, .. }
+            | Self::Anonymous { common, .. }
+            | Self::OAuth2 { common, .. }
+            | Self::ServiceAccount { common }
+            | Self::ServicePrincipal { common, .. }
+            | Self::ImpersonateAccount { common, .. } => common,
         }
     }
 
-    #[must_use]
+    fn scrub(&mut self, policy: ScrubPolicy) {
+        match self {
+            Self::Key { key, .. } => *key = None,
+            Self::UsernamePassword {
+                username, password, ..
+            }
+            | Self::ImpersonateAccount {
+                username, password, ..
+            } => {
+                *password = None;
+                if policy.usernames {
+                    *username = REDACTED.to_string();
+                }
+            }
+            Self::Windows {
+                username, password, ..
+            } => {
+                *password = None;
+                if policy.usernames {
+                    redact(username);
+                }
+            }
+            Self::OAuth2 {
+                access_token,
+                refresh_token,
+                ..
+            } => {
+                *access_token = None;
+                *refresh_token = None;
+            }
+            Self::ServicePrincipal { client_id, .. } => {
+                if policy.usernames {
+                    *client_id = REDACTED.to_string();
+                }
+            }
+            Self::Anonymous { .. } | Self::ServiceAccount { .. } => {}
+        }
+
+        let common = self.common_mut();
+        common.unknown.retain(|name| !scrub::is_secret(name));
+        if policy.addresses {
+            common.path = REDACTED.to_string();
+        }
+    }
+
+    /// The names of the properties holding secrets.
+    fn secrets(&self) -> Vec<String> {
+        let modelled = match self {
+            Self::Key { key, .. } => vec![("Key", key)],
+            Self::UsernamePassword { password, .. }
+            | Self::Windows { password, .. }
+            | Self::ImpersonateAccount { password, .. } => vec![("Password", password)],
+            Self::OAuth2 {
+                access_token,
+                refresh_token,
+                ..
+            } => vec![
+                ("AccessToken", access_token),
+                ("RefreshToken", refresh_token),
+            ],
+            Self::Anonymous { .. }
+            | Self::ServiceAccount { .. }
+            | Self::ServicePrincipal { .. } => Vec::new(),
+        };
+
+        modelled
+            .into_iter()
+            .filter(|(_, value)| value.is_some())
+            .map(|(name, _)| name.to_string())
+            .chain(
+                self.common()
+                    .unknown
+                    .keys()
+                    .filter(|name| scrub::is_secret(name))
+                    .cloned(),
+            )
+            .collect()
+    }
+}
+
+impl Credential for CredentialType {
+    fn kind(&self) -> String {
+        self.common().kind.clone()
+    }
+
+    fn path(&self) -> String {
+        self.common().path.clone()
+    }
+
     fn authentication(&self) -> Authentication {
         match self {
             Self::Key { .. } => Authentication::Key,
             Self::UsernamePassword { .. } => Authentication::UsernamePassword,
+            Self::Windows { .. } => Authentication::Windows,
+            Self::Anonymous { .. } => Authentication::Anonymous,
+            Self::OAuth2 { .. } => Authentication::OAuth2,
+            Self::ServiceAccount { .. } => Authentication::ServiceAccount,
+            Self::ServicePrincipal { .. } => Authentication::ServicePrincipal,
+            Self::ImpersonateAccount { .. } => Authentication::ImpersonateAccount,
+        }
+    }
+
+    fn username(&self) -> Option<String> {
+        match self {
+            Self::UsernamePassword { username, .. } | Self::ImpersonateAccount { username, .. } => {
+                Some(username.clone())
+            }
+            Self::Windows { username, .. } => username.clone(),
+            Self::ServicePrincipal { client_id, .. } => Some(client_id.clone()),
+            Self::Key { .. }
+            | Self::Anonymous { .. }
+            | Self::OAuth2 { .. }
+            | Self::ServiceAccount { .. } => None,
         }
     }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct DataSourceOption {
     #[serde(skip_serializing_if = "Option::is_none")]
     return_single_database: Option<bool>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
 }
 
 #[cfg(test)]
@@ -213,15 +795,16 @@ mod test {
     #[test]
     fn test_can_sort_vec_of_datasources() {
         let mut datasources = vec![
-            DataSource {
-                type_: "structured".to_string(),
+            DataSource::Structured(StructuredDataSource {
                 name: "Zero Datasource".to_string(),
                 connection_details: ConnectionDetails::DocumentDb {
                     address: Address::DocumentDb {
                         url: "http://google.com".to_string(),
                         database: Some("TheDB".to_string()),
                         collection: Some("Default".to_string()),
+                        unknown: UnknownFields::default(),
                     },
+                    unknown: UnknownFields::default(),
                 },
                 options: None,
                 credential: CredentialType::Key {
@@ -229,18 +812,22 @@ mod test {
                         kind: "DocumentDb".to_string(),
                         path: "http://google.com".to_string(),
                         privacy_setting: None,
+                        unknown: UnknownFields::default(),
                     },
+                    key: None,
                 },
-            },
-            DataSource {
-                type_: "structured".to_string(),
+                unknown: UnknownFields::default(),
+            }),
+            DataSource::Structured(StructuredDataSource {
                 name: "A Datasource".to_string(),
                 connection_details: ConnectionDetails::DocumentDb {
                     address: Address::DocumentDb {
                         url: "http://google.com".to_string(),
                         database: Some("TheDB".to_string()),
                         collection: Some("Default".to_string()),
+                        unknown: UnknownFields::default(),
                     },
+                    unknown: UnknownFields::default(),
                 },
                 options: None,
                 credential: CredentialType::Key {
@@ -248,21 +835,25 @@ mod test {
                         kind: "DocumentDb".to_string(),
                         path: "http://google.com".to_string(),
                         privacy_setting: None,
+                        unknown: UnknownFields::default(),
                     },
+                    key: None,
                 },
-            },
+                unknown: UnknownFields::default(),
+            }),
         ];
 
         let expected = vec![
-            DataSource {
-                type_: "structured".to_string(),
+            DataSource::Structured(StructuredDataSource {
                 name: "Zero Datasource".to_string(),
                 connection_details: ConnectionDetails::DocumentDb {
                     address: Address::DocumentDb {
                         url: "http://google.com".to_string(),
                         database: Some("TheDB".to_string()),
                         collection: Some("Default".to_string()),
+                        unknown: UnknownFields::default(),
                     },
+                    unknown: UnknownFields::default(),
                 },
                 options: None,
                 credential: CredentialType::Key {
@@ -270,18 +861,22 @@ mod test {
                         kind: "DocumentDb".to_string(),
                         path: "http://google.com".to_string(),
                         privacy_setting: None,
+                        unknown: UnknownFields::default(),
                     },
+                    key: None,
                 },
-            },
-            DataSource {
-                type_: "structured".to_string(),
+                unknown: UnknownFields::default(),
+            }),
+            DataSource::Structured(StructuredDataSource {
                 name: "A Datasource".to_string(),
                 connection_details: ConnectionDetails::DocumentDb {
                     address: Address::DocumentDb {
                         url: "http://google.com".to_string(),
                         database: Some("TheDB".to_string()),
                         collection: Some("Default".to_string()),
+                        unknown: UnknownFields::default(),
                     },
+                    unknown: UnknownFields::default(),
                 },
                 options: None,
                 credential: CredentialType::Key {
@@ -289,9 +884,12 @@ mod test {
                         kind: "DocumentDb".to_string(),
                         path: "http://google.com".to_string(),
                         privacy_setting: None,
+                        unknown: UnknownFields::default(),
                     },
+                    key: None,
                 },
-            },
+                unknown: UnknownFields::default(),
+            }),
         ];
 
         datasources.sort();
@@ -435,4 +1033,599 @@ mod test {
 
         there_and_back_test(&datasource, DataSource::from_value);
     }
+
+    #[test]
+    fn credentials_preserve_unknown_fields_on_request() {
+        use crate::models::unknown::preserving_unknown;
+
+        let input = json!(
+            {
+                "AuthenticationKind": "UsernamePassword",
+                "kind": "SQL",
+                "path": "<url>",
+                "Username": "username",
+                "EncryptConnection": true,
+                "TrustServerCertificate": true
+            }
+        );
+
+        preserving_unknown(true, || {
+            there_and_back_test(&input, CredentialType::from_value);
+        });
+    }
+
+    #[test]
+    fn readwrite_provider_datasource() {
+        let data = json!(
+            {
+                "type": "provider",
+                "name": "SqlServer localhost AdventureWorksDW",
+                "connectionString": "Provider=SQLNCLI11;Data Source=localhost;Initial Catalog=AdventureWorksDW;Integrated Security=SSPI;Persist Security Info=false",
+                "impersonationMode": "impersonateAccount",
+                "account": "DOMAIN\\svc-ssas",
+                "timeout": 600,
+                "provider": "System.Data.SqlClient"
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn provider_and_structured_datasources_sort_by_name() {
+        let mut datasources = [
+            DataSource::from_value(&json!(
+                {
+                    "type": "provider",
+                    "name": "Warehouse",
+                    "connectionString": "Data Source=localhost"
+                }
+            )),
+            DataSource::from_value(&json!(
+                {
+                    "type": "structured",
+                    "name": "analytics",
+                    "connectionDetails": {
+                        "protocol": "tds",
+                        "address": {"server": "localhost", "database": "Analytics"},
+                        "authentication": null,
+                        "query": null
+                    },
+                    "credential": {
+                        "AuthenticationKind": "Key",
+                        "kind": "SQL",
+                        "path": "localhost;Analytics"
+                    }
+                }
+            )),
+        ];
+
+        datasources.sort();
+
+        let names: Vec<_> = datasources.iter().map(DataSource::name).collect();
+        assert_eq!(names, ["analytics", "Warehouse"]);
+    }
+
+    #[test]
+    fn readwrite_oracle_datasource() {
+        let data = json!(
+            {
+                "type": "structured",
+                "name": "Oracle/orcl.example.com:1521/SALES",
+                "connectionDetails": {
+                    "protocol": "oracle",
+                    "address": {"server": "orcl.example.com:1521/SALES"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "UsernamePassword",
+                    "kind": "Oracle",
+                    "path": "orcl.example.com:1521/SALES",
+                    "Username": "username",
+                    "EncryptConnection": true
+                }
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn readwrite_odbc_datasource() {
+        let data = json!(
+            {
+                "type": "structured",
+                "name": "ODBC/dsn=Warehouse",
+                "connectionDetails": {
+                    "protocol": "odbc",
+                    "address": {"options": {"dsn": "Warehouse"}},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "UsernamePassword",
+                    "kind": "ODBC",
+                    "path": "dsn=warehouse",
+                    "Username": "username",
+                    "EncryptConnection": true
+                }
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn readwrite_oledb_datasource() {
+        let data = json!(
+            {
+                "type": "structured",
+                "name": "OLEDB/Warehouse",
+                "connectionDetails": {
+                    "protocol": "oledb",
+                    "address": {"connectionString": "Provider=MSOLEDBSQL;Data Source=localhost;Initial Catalog=Warehouse"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "UsernamePassword",
+                    "kind": "OleDb",
+                    "path": "provider=msoledbsql;data source=localhost;initial catalog=warehouse",
+                    "Username": "username",
+                    "EncryptConnection": true
+                }
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn readwrite_analysis_services_datasource() {
+        let data = json!(
+            {
+                "type": "structured",
+                "name": "AnalysisServices/asazure://westeurope.asazure.windows.net/sales;Sales",
+                "connectionDetails": {
+                    "protocol": "analysis-services",
+                    "address": {"server": "asazure://westeurope.asazure.windows.net/sales", "database": "Sales"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "UsernamePassword",
+                    "kind": "AnalysisServices",
+                    "path": "asazure://westeurope.asazure.windows.net/sales",
+                    "Username": "username",
+                    "EncryptConnection": true
+                }
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn readwrite_azure_blobs_datasource() {
+        let data = json!(
+            {
+                "type": "structured",
+                "name": "AzureBlobs/https://mystorage.blob.core.windows.net/",
+                "connectionDetails": {
+                    "protocol": "azure-blobs",
+                    "address": {"account": "mystorage", "domain": "blob.core.windows.net"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "Key",
+                    "kind": "AzureBlobs",
+                    "path": "https://mystorage.blob.core.windows.net/"
+                }
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn readwrite_azure_data_lake_storage_datasource() {
+        let data = json!(
+            {
+                "type": "structured",
+                "name": "AzureDataLakeStorage/https://mylake.dfs.core.windows.net/raw",
+                "connectionDetails": {
+                    "protocol": "azure-data-lake-storage",
+                    "address": {"url": "https://mylake.dfs.core.windows.net/raw"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "Key",
+                    "kind": "AzureDataLakeStorage",
+                    "path": "https://mylake.dfs.core.windows.net/raw"
+                }
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn readwrite_file_datasource() {
+        let data = json!(
+            {
+                "type": "structured",
+                "name": "File/C:\\Data\\Sales.xlsx",
+                "connectionDetails": {
+                    "protocol": "file",
+                    "address": {"path": "C:\\Data\\Sales.xlsx"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "Key",
+                    "kind": "File",
+                    "path": "c:\\data\\sales.xlsx"
+                }
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn readwrite_folder_datasource() {
+        let data = json!(
+            {
+                "type": "structured",
+                "name": "Folder/\\\\fileserver\\exports",
+                "connectionDetails": {
+                    "protocol": "folder",
+                    "address": {"path": "\\\\fileserver\\exports"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "Key",
+                    "kind": "Folder",
+                    "path": "\\\\fileserver\\exports"
+                }
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn readwrite_odata_datasource() {
+        let data = json!(
+            {
+                "type": "structured",
+                "name": "OData/https://services.odata.org/V4/Northwind/Northwind.svc",
+                "connectionDetails": {
+                    "protocol": "odata",
+                    "address": {"url": "https://services.odata.org/V4/Northwind/Northwind.svc"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "Key",
+                    "kind": "OData",
+                    "path": "https://services.odata.org/V4/Northwind/Northwind.svc"
+                }
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn readwrite_sharepoint_datasource() {
+        let data = json!(
+            {
+                "type": "structured",
+                "name": "SharePoint/https://contoso.sharepoint.com/sites/finance",
+                "connectionDetails": {
+                    "protocol": "sharepoint",
+                    "address": {"url": "https://contoso.sharepoint.com/sites/finance"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "Key",
+                    "kind": "SharePoint",
+                    "path": "https://contoso.sharepoint.com/sites/finance"
+                }
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn readwrite_web_datasource() {
+        let data = json!(
+            {
+                "type": "structured",
+                "name": "Web/https://example.com/rates.csv",
+                "connectionDetails": {
+                    "protocol": "web",
+                    "address": {"url": "https://example.com/rates.csv"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "Key",
+                    "kind": "Web",
+                    "path": "https://example.com/rates.csv"
+                }
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn readwrite_snowflake_datasource() {
+        let data = json!(
+            {
+                "type": "structured",
+                "name": "Snowflake/contoso.snowflakecomputing.com;ANALYTICS_WH",
+                "connectionDetails": {
+                    "protocol": "snowflake",
+                    "address": {"server": "contoso.snowflakecomputing.com", "warehouse": "ANALYTICS_WH", "database": "SALES"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "UsernamePassword",
+                    "kind": "Snowflake",
+                    "path": "contoso.snowflakecomputing.com;ANALYTICS_WH",
+                    "Username": "username",
+                    "EncryptConnection": true
+                }
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn readwrite_databricks_datasource() {
+        let data = json!(
+            {
+                "type": "structured",
+                "name": "Databricks/adb-1234567890123456.7.azuredatabricks.net",
+                "connectionDetails": {
+                    "protocol": "databricks",
+                    "address": {"host": "adb-1234567890123456.7.azuredatabricks.net", "httpPath": "/sql/1.0/warehouses/abc123"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "Key",
+                    "kind": "Databricks",
+                    "path": "adb-1234567890123456.7.azuredatabricks.net"
+                }
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn every_credential_kind_has_kind_path_and_authentication() {
+        let cases = [
+            (
+                json!({"AuthenticationKind": "Windows", "kind": "File", "path": "c:\\data\\sales.xlsx", "Username": "DOMAIN\\reader"}),
+                Authentication::Windows,
+                Some("DOMAIN\\reader"),
+            ),
+            (
+                json!({"AuthenticationKind": "Anonymous", "kind": "Web", "path": "https://example.com/rates.csv"}),
+                Authentication::Anonymous,
+                None,
+            ),
+            (
+                json!({"AuthenticationKind": "OAuth2", "kind": "SharePoint", "path": "https://contoso.sharepoint.com/sites/finance", "Expires": "Tue, 01 Aug 2023 10:00:00 GMT"}),
+                Authentication::OAuth2,
+                None,
+            ),
+            (
+                json!({"AuthenticationKind": "ServiceAccount", "kind": "AnalysisServices", "path": "asazure://westeurope.asazure.windows.net/sales"}),
+                Authentication::ServiceAccount,
+                None,
+            ),
+            (
+                json!({"AuthenticationKind": "ServicePrincipal", "kind": "Databricks", "path": "adb-1234567890123456.7.azuredatabricks.net", "TenantId": "72f988bf-86f1-41af-91ab-2d7cd011db47", "ServicePrincipalClientId": "2e0a9d05-7c43-4b3c-9a64-8a1f1e6a3d21"}),
+                Authentication::ServicePrincipal,
+                Some("2e0a9d05-7c43-4b3c-9a64-8a1f1e6a3d21"),
+            ),
+            (
+                json!({"AuthenticationKind": "ImpersonateAccount", "kind": "Folder", "path": "\\\\fileserver\\exports", "Username": "DOMAIN\\svc-ssas"}),
+                Authentication::ImpersonateAccount,
+                Some("DOMAIN\\svc-ssas"),
+            ),
+        ];
+
+        for (input, authentication, username) in cases {
+            there_and_back_test(&input, CredentialType::from_value);
+
+            let credential = CredentialType::from_value(&input);
+            assert_eq!(credential.kind(), input["kind"].as_str().unwrap());
+            assert_eq!(credential.path(), input["path"].as_str().unwrap());
+            assert_eq!(credential.authentication(), authentication);
+            assert_eq!(credential.username().as_deref(), username);
+        }
+    }
+
+    #[test]
+    fn readwrite_windows_credential_datasource() {
+        let data = json!(
+            {
+                "type": "structured",
+                "name": "Folder/\\\\fileserver\\exports",
+                "connectionDetails": {
+                    "protocol": "folder",
+                    "address": {"path": "\\\\fileserver\\exports"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "Windows",
+                    "kind": "Folder",
+                    "path": "\\\\fileserver\\exports",
+                    "Username": "DOMAIN\\reader",
+                    "PrivacySetting": "Organizational"
+                }
+            }
+        );
+
+        there_and_back_test(&data, DataSource::from_value);
+    }
+
+    #[test]
+    fn scrubbing_removes_secrets_and_redacts_usernames() {
+        let mut datasource = DataSource::from_value(&json!(
+            {
+                "type": "structured",
+                "name": "SQL/prod-sql;Sales",
+                "connectionDetails": {
+                    "protocol": "tds",
+                    "address": {"server": "prod-sql", "database": "Sales"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "UsernamePassword",
+                    "kind": "SQL",
+                    "path": "prod-sql;Sales",
+                    "Username": "loader",
+                    "Password": "hunter2",
+                    "EncryptConnection": true
+                }
+            }
+        ));
+        assert_eq!(datasource.secrets(), ["credential Password"]);
+
+        datasource.scrub(ScrubPolicy {
+            usernames: true,
+            addresses: false,
+        });
+
+        assert!(datasource.secrets().is_empty());
+        assert_eq!(
+            serde_json::to_value(&datasource).unwrap()["credential"],
+            json!(
+                {
+                    "AuthenticationKind": "UsernamePassword",
+                    "kind": "SQL",
+                    "path": "prod-sql;Sales",
+                    "Username": "<redacted>",
+                    "EncryptConnection": true
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn scrubbing_addresses_redacts_servers_and_paths() {
+        let mut datasource = DataSource::from_value(&json!(
+            {
+                "type": "structured",
+                "name": "Snowflake",
+                "connectionDetails": {
+                    "protocol": "snowflake",
+                    "address": {"server": "contoso.snowflakecomputing.com", "warehouse": "ANALYTICS_WH"},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {
+                    "AuthenticationKind": "OAuth2",
+                    "kind": "Snowflake",
+                    "path": "contoso.snowflakecomputing.com;ANALYTICS_WH",
+                    "AccessToken": "eyJ0eXAi",
+                    "RefreshToken": "0.AQk"
+                }
+            }
+        ));
+        let policy = ScrubPolicy {
+            usernames: true,
+            addresses: true,
+        };
+
+        datasource.scrub(policy);
+
+        let value = serde_json::to_value(&datasource).unwrap();
+        assert_eq!(
+            value["connectionDetails"]["address"],
+            json!({"server": "<redacted>", "warehouse": "<redacted>"})
+        );
+        assert_eq!(
+            value["credential"],
+            json!({"AuthenticationKind": "OAuth2", "kind": "Snowflake", "path": "<redacted>"})
+        );
+    }
+
+    #[test]
+    fn provider_datasources_report_connection_string_passwords() {
+        let mut datasource = DataSource::from_value(&json!(
+            {
+                "type": "provider",
+                "name": "Warehouse",
+                "connectionString": "Data Source=prod-sql;User ID=loader;Password=hunter2",
+                "account": "DOMAIN\\svc-ssas"
+            }
+        ));
+        assert_eq!(datasource.secrets(), ["connectionString Password"]);
+
+        datasource.scrub(ScrubPolicy {
+            usernames: true,
+            addresses: false,
+        });
+
+        assert!(datasource.secrets().is_empty());
+        assert_eq!(
+            serde_json::to_value(&datasource).unwrap(),
+            json!(
+                {
+                    "type": "provider",
+                    "name": "Warehouse",
+                    "connectionString": "Data Source=prod-sql;User ID=<redacted>",
+                    "account": "<redacted>"
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn odbc_and_oledb_connection_secrets_are_found_and_removed() {
+        let mut odbc = DataSource::from_value(&json!(
+            {
+                "type": "structured",
+                "name": "ODBC/dsn=Warehouse",
+                "connectionDetails": {
+                    "protocol": "odbc",
+                    "address": {"options": {"dsn": "Warehouse", "UID": "loader", "pwd": "hunter2"}},
+                    "authentication": null,
+                    "query": null
+                },
+                "credential": {"AuthenticationKind": "Anonymous", "kind": "ODBC", "path": "dsn=warehouse"}
+            }
+        ));
+        assert_eq!(odbc.secrets(), ["connectionDetails pwd"]);
+
+        odbc.scrub(ScrubPolicy {
+            usernames: true,
+            addresses: false,
+        });
+
+        assert!(odbc.secrets().is_empty());
+        assert_eq!(
+            serde_json::to_value(&odbc).unwrap()["connectionDetails"]["address"],
+            json!({"options": {"dsn": "Warehouse", "UID": "<redacted>"}})
+        );
+    }
 }
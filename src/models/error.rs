@@ -0,0 +1,169 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::fmt;
+
+use serde_json::Value;
+use serde_path_to_error::Segment;
+
+/// An error locating where in a bim file parsing failed.
+#[derive(Debug)]
+pub struct ParseError {
+    /// The JSON path of the offending value, e.g. `model.tables[12].columns[3]`.
+    pub path: String,
+
+    /// The name of the innermost named object on [`path`](Self::path).
+    pub object: Option<String>,
+
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(error: serde_path_to_error::Error<serde_json::Error>, source: &str) -> Self {
+        let object = serde_json::from_str(source)
+            .ok()
+            .and_then(|document| innermost_name(&document, error.path()));
+        let path = error.path().to_string();
+        let inner = Self::from(error.into_inner());
+
+        Self {
+            // An unknown path means the failure was in the document structure.
+            path: if path == "?" {
+                inner.path.clone()
+            } else {
+                path
+            },
+            object,
+            ..inner
+        }
+    }
+}
+
+impl From<serde_json::Error> for ParseError {
+    fn from(error: serde_json::Error) -> Self {
+        let location = format!(" at line {} column {}", error.line(), error.column());
+        let message = error.to_string();
+
+        Self {
+            path: String::from("."),
+            object: None,
+            line: error.line(),
+            column: error.column(),
+            message: message
+                .strip_suffix(&location)
+                .unwrap_or(&message)
+                .to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path != "." {
+            write!(f, "{}", self.path)?;
+            if let Some(object) = &self.object {
+                write!(f, " ({object:?})")?;
+            }
+            write!(f, " ")?;
+        }
+        write!(
+            f,
+            "at line {} column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn innermost_name(document: &Value, path: &serde_path_to_error::Path) -> Option<String> {
+    let mut value = document;
+    let mut name = None;
+
+    for segment in path.iter() {
+        let next = match segment {
+            Segment::Seq { index } => value.get(index),
+            Segment::Map { key } => value.get(key),
+            Segment::Enum { .. } | Segment::Unknown => None,
+        };
+        match next {
+            Some(next) => value = next,
+            None => break,
+        }
+        if let Some(n) = value.get("name").and_then(Value::as_str) {
+            name = Some(n.to_string());
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use serde_json::{json, Value};
+
+    use crate::models::test::bim_value;
+    use crate::models::{Bim, ParseError};
+    use crate::Error;
+
+    fn parse_error(input: &str) -> ParseError {
+        match Bim::from_str(input) {
+            Err(Error::Parse(error)) => error,
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+    }
+
+    fn bim_with_column(column: &Value) -> String {
+        let tables = json!([{"name": "Sales", "columns": [column], "partitions": []}]);
+        serde_json::to_string_pretty(&bim_value(json!({"tables": tables}))).unwrap()
+    }
+
+    #[test]
+    fn errors_name_the_path_and_object() {
+        let input = bim_with_column(&json!({"name": "Amount", "dataType": 5, "sourceColumn": "x"}));
+
+        let error = parse_error(&input);
+
+        assert_eq!(error.path, "model.tables[0].columns[0]");
+        assert_eq!(error.object.as_deref(), Some("Amount"));
+        assert_eq!(error.line, 16);
+    }
+
+    #[test]
+    fn untagged_errors_explain_each_variant() {
+        let input = bim_with_column(&json!({"name": "Amount", "dataType": "int64"}));
+
+        let error = parse_error(&input);
+
+        assert!(error.message.contains("Calculated: missing field `type`"));
+        assert!(error
+            .message
+            .contains("Sourced: missing field `sourceColumn`"));
+    }
+
+    #[test]
+    fn root_errors_have_no_path() {
+        let error = parse_error("{");
+
+        assert_eq!(error.path, ".");
+        assert!(error.to_string().starts_with("at line 1 column 1: "));
+    }
+}
@@ -16,15 +16,48 @@ You should have received a copy of the GNU General Public License
 along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, Eq, Clone)]
-#[serde(untagged, deny_unknown_fields)]
+use super::unknown::UnknownFields;
+use super::untagged::{deserialize_untagged, variant};
+
+#[derive(Serialize, Debug, Eq, Clone)]
+#[serde(untagged)]
 pub enum Expression {
     Vec(Vec<String>),
     String(String),
 }
 
+impl<'de> Deserialize<'de> for Expression {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_untagged(
+            deserializer,
+            "Expression",
+            &[
+                ("Vec", |v| variant(v).map(Self::Vec)),
+                ("String", |v| variant(v).map(Self::String)),
+            ],
+        )
+    }
+}
+
+impl Expression {
+    /// Replaces string literals line by line, see
+    /// [`replace_literals`](super::scrub::replace_literals).
+    pub(crate) fn replace_literals(&mut self, replacements: &[(String, String)]) {
+        let lines = match self {
+            Self::Vec(lines) => lines.iter_mut().collect(),
+            Self::String(s) => vec![s],
+        };
+        for line in lines {
+            *line = super::scrub::replace_literals(line, replacements);
+        }
+    }
+}
+
 impl std::fmt::Display for Expression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let output = match self {
@@ -47,12 +80,38 @@ pub trait Expressive {
 
 #[allow(clippy::module_name_repetitions)]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(deny_unknown_fields)]
 pub struct ModelExpression {
     pub name: String,
 
     pub kind: String,
     expression: Expression,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl ModelExpression {
+    /// Whether this is a Power Query parameter, e.g.
+    /// `"localhost" meta [IsParameterQuery=true, Type="Text"]`.
+    #[must_use]
+    pub fn is_parameter(&self) -> bool {
+        self.expression
+            .to_string()
+            .replace(' ', "")
+            .contains("IsParameterQuery=true")
+    }
+
+    pub(crate) const fn raw_expression(&self) -> &Expression {
+        &self.expression
+    }
+
+    pub(crate) fn set_expression(&mut self, expression: Expression) {
+        self.expression = expression;
+    }
+
+    pub(crate) fn replace_literals(&mut self, replacements: &[(String, String)]) {
+        self.expression.replace_literals(replacements);
+    }
 }
 
 impl Ord for ModelExpression {
@@ -68,7 +127,6 @@ impl PartialOrd for ModelExpression {
 }
 
 impl Expressive for ModelExpression {
-    #[must_use]
     fn expression(&self) -> Option<String> {
         Some(self.expression.to_string())
     }
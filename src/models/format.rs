@@ -0,0 +1,224 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+use serde_json::ser::{CompactFormatter, Formatter, PrettyFormatter};
+
+/// The JSON formatting conventions used when writing a bim file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// `serde_json`'s pretty printer, as written by earlier versions.
+    #[default]
+    Serde,
+
+    /// Visual Studio, which escapes non-ASCII characters and `<>&'`.
+    VisualStudio,
+
+    /// Tabular Editor, which escapes the line separators `U+0085`, `U+2028`
+    /// and `U+2029` but otherwise writes characters as they are.
+    TabularEditor,
+
+    /// A single line without whitespace.
+    Compact,
+}
+
+/// Characters escaped in strings, beyond those JSON requires.
+#[derive(Clone, Copy)]
+enum Escape {
+    Required,
+    LineSeparators,
+    NonAsciiAndHtml,
+}
+
+impl Escape {
+    fn needs_escape(self, c: char) -> bool {
+        match self {
+            Self::Required => false,
+            Self::LineSeparators => matches!(c, '\u{85}' | '\u{2028}' | '\u{2029}'),
+            Self::NonAsciiAndHtml => !c.is_ascii() || matches!(c, '<' | '>' | '&' | '\''),
+        }
+    }
+}
+
+/// Lays out values with `F` and escapes strings as .NET's JSON writers do.
+struct StyleFormatter<F> {
+    inner: F,
+    escape: Escape,
+}
+
+/// Serializes `value` as JSON in the given style.
+pub(crate) fn to_string<T: Serialize>(value: &T, style: Style) -> serde_json::Result<String> {
+    let mut output = Vec::with_capacity(128);
+
+    match style {
+        Style::Serde => write(&mut output, value, PrettyFormatter::new(), Escape::Required),
+        Style::VisualStudio => write(
+            &mut output,
+            value,
+            PrettyFormatter::new(),
+            Escape::NonAsciiAndHtml,
+        ),
+        Style::TabularEditor => write(
+            &mut output,
+            value,
+            PrettyFormatter::new(),
+            Escape::LineSeparators,
+        ),
+        Style::Compact => write(&mut output, value, CompactFormatter, Escape::Required),
+    }?;
+
+    // The formatters only write valid UTF-8.
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+fn write<T: Serialize, F: Formatter>(
+    output: &mut Vec<u8>,
+    value: &T,
+    inner: F,
+    escape: Escape,
+) -> serde_json::Result<()> {
+    let formatter = StyleFormatter { inner, escape };
+    let mut serializer = serde_json::Serializer::with_formatter(output, formatter);
+    value.serialize(&mut serializer)
+}
+
+impl<F: Formatter> Formatter for StyleFormatter<F> {
+    fn write_string_fragment<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        fragment: &str,
+    ) -> io::Result<()> {
+        let mut start = 0;
+
+        for (i, c) in fragment.char_indices() {
+            if self.escape.needs_escape(c) {
+                writer.write_all(&fragment.as_bytes()[start..i])?;
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    write!(writer, "\\u{unit:04x}")?;
+                }
+                start = i + c.len_utf8();
+            }
+        }
+        writer.write_all(&fragment.as_bytes()[start..])
+    }
+
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.begin_array(writer)
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.end_array(writer)
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.inner.begin_array_value(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.end_array_value(writer)
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.begin_object(writer)
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.end_object(writer)
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.inner.begin_object_key(writer, first)
+    }
+
+    fn end_object_key<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.end_object_key(writer)
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.begin_object_value(writer)
+    }
+
+    fn end_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.inner.end_object_value(writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> serde_json::Value {
+        json!({
+            "name": "Café <Sales> & 'Returns'",
+            "expression": "\u{2028}😀",
+            "columns": [],
+            "annotations": [{"value": 1}]
+        })
+    }
+
+    #[test]
+    fn serde_style_matches_to_string_pretty() {
+        let value = sample();
+
+        assert_eq!(
+            to_string(&value, Style::Serde).unwrap(),
+            serde_json::to_string_pretty(&value).unwrap()
+        );
+    }
+
+    #[test]
+    fn visual_studio_style_escapes_non_ascii_and_html() {
+        let output = to_string(&sample(), Style::VisualStudio).unwrap();
+
+        assert!(
+            output.contains(r#""name": "Caf\u00e9 \u003cSales\u003e \u0026 \u0027Returns\u0027""#)
+        );
+        assert!(output.contains(r#""expression": "\u2028\ud83d\ude00""#));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&output).unwrap(),
+            sample()
+        );
+    }
+
+    #[test]
+    fn tabular_editor_style_escapes_line_separators() {
+        let output = to_string(&sample(), Style::TabularEditor).unwrap();
+
+        assert!(output.contains(r#""name": "Café <Sales> & 'Returns'""#));
+        assert!(output.contains(r#""expression": "\u2028😀""#));
+    }
+
+    #[test]
+    fn compact_style_has_no_whitespace() {
+        let output = to_string(&json!({"a": [1, 2], "b": {}}), Style::Compact).unwrap();
+
+        assert_eq!(output, r#"{"a":[1,2],"b":{}}"#);
+    }
+}
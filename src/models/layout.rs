@@ -0,0 +1,173 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::format::Style;
+
+const BOM: char = '\u{feff}';
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+/// How the text of a bim file is laid out, independent of its JSON content.
+///
+/// Visual Studio writes bim files as UTF-8 with a byte order mark and CRLF
+/// line endings, and escapes characters other tools write as they are, so
+/// these are detected when reading and reproduced on write.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    /// Whether the file starts with a UTF-8 byte order mark.
+    pub bom: bool,
+    pub line_ending: LineEnding,
+
+    /// Whether the file ends with a line ending.
+    pub trailing_newline: bool,
+
+    /// How the JSON itself is formatted, detected from its line breaks and
+    /// escaped characters.
+    pub style: Style,
+}
+
+impl Layout {
+    /// Detects the layout of `contents`, using the first line ending found.
+    #[must_use]
+    pub fn detect(contents: &str) -> Self {
+        let line_ending = match contents.find('\n') {
+            Some(i) if contents[..i].ends_with('\r') => LineEnding::CrLf,
+            _ => LineEnding::Lf,
+        };
+
+        Self {
+            bom: contents.starts_with(BOM),
+            line_ending,
+            trailing_newline: contents.ends_with('\n'),
+            style: detect_style(contents),
+        }
+    }
+
+    /// Removes a leading byte order mark, which JSON parsers reject.
+    #[must_use]
+    pub fn strip_bom(contents: &str) -> &str {
+        contents.strip_prefix(BOM).unwrap_or(contents)
+    }
+
+    /// Lays out `json`, which must use `\n` line endings, in this layout.
+    /// The [`style`](Self::style) has already been applied by the serializer.
+    #[must_use]
+    pub fn apply(&self, json: &str) -> String {
+        let mut output = String::with_capacity(json.len() + json.len() / 16);
+
+        if self.bom {
+            output.push(BOM);
+        }
+        match self.line_ending {
+            LineEnding::Lf => output.push_str(json),
+            LineEnding::CrLf => output.push_str(&json.replace('\n', "\r\n")),
+        }
+        if self.trailing_newline {
+            output.push_str(match self.line_ending {
+                LineEnding::Lf => "\n",
+                LineEnding::CrLf => "\r\n",
+            });
+        }
+        output
+    }
+}
+
+/// Detects the style from the characters escaped in strings. Files without
+/// any escapes that tell the styles apart are written the same way by all of
+/// the pretty printed styles, so these are reported as [`Style::Serde`].
+fn detect_style(contents: &str) -> Style {
+    if !contents.trim_end().contains('\n') {
+        return Style::Compact;
+    }
+
+    let mut style = Style::Serde;
+    let mut chars = contents.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            continue;
+        }
+        if chars.next() != Some('u') {
+            continue;
+        }
+        let hex: String = chars.by_ref().take(4).collect();
+        match u32::from_str_radix(&hex, 16) {
+            Ok(0x85 | 0x2028 | 0x2029) => style = Style::TabularEditor,
+            Ok(0x26 | 0x27 | 0x3c | 0x3e | 0x80..) => return Style::VisualStudio,
+            _ => {}
+        }
+    }
+    style
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detects_visual_studio_layout() {
+        let layout = Layout::detect("\u{feff}{\r\n  \"name\": \"Model\"\r\n}\r\n");
+
+        assert_eq!(
+            layout,
+            Layout {
+                bom: true,
+                line_ending: LineEnding::CrLf,
+                trailing_newline: true,
+                style: Style::Serde,
+            }
+        );
+    }
+
+    #[test]
+    fn default_layout_matches_plain_json() {
+        let json = "{\n  \"name\": \"Model\"\n}";
+
+        assert_eq!(Layout::detect(json), Layout::default());
+        assert_eq!(Layout::default().apply(json), json);
+    }
+
+    #[test]
+    fn apply_reproduces_detected_layout() {
+        let contents = "\u{feff}{\r\n  \"name\": \"Model\"\r\n}\r\n";
+        let json = "{\n  \"name\": \"Model\"\n}";
+
+        assert_eq!(Layout::detect(contents).apply(json), contents);
+    }
+
+    #[test]
+    fn detects_style_from_escapes_and_line_breaks() {
+        let style = |contents| Layout::detect(contents).style;
+
+        assert_eq!(
+            style("{\n  \"name\": \"Caf\\u00e9\"\n}"),
+            Style::VisualStudio
+        );
+        assert_eq!(style("{\n  \"name\": \"\\u0026\"\n}"), Style::VisualStudio);
+        assert_eq!(
+            style("{\n  \"name\": \"Café\\u2028\"\n}"),
+            Style::TabularEditor
+        );
+        assert_eq!(style("{\n  \"name\": \"Café \\\\u0026\"\n}"), Style::Serde);
+        assert_eq!(style("{\"name\":\"Model\"}\n"), Style::Compact);
+    }
+}
@@ -18,22 +18,34 @@ along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 pub mod annotations;
 mod bim;
+pub mod culture;
 pub mod datasource;
+mod error;
 pub mod expression;
+pub mod format;
+pub mod layout;
 pub mod model;
+pub mod perspective;
 pub mod relationship;
 pub mod roles;
+pub mod scrub;
 pub mod skip_if;
 pub mod table;
 mod traits;
+pub mod unknown;
+mod untagged;
 
 //test helper functions
 #[cfg(test)]
-mod test;
+pub(crate) mod test;
 
-pub use bim::Bim;
+pub use bim::{Bim, ReadOptions, WriteOptions};
+pub use culture::Culture;
 pub use datasource::DataSource;
+pub use error::ParseError;
 pub use expression::{Expression, Expressive};
 pub use model::Model;
+pub use perspective::Perspective;
 pub use relationship::Relationship;
+pub use scrub::ScrubPolicy;
 pub use traits::RecursiveSort;
@@ -17,16 +17,20 @@ along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
 use super::annotations::Annotation;
+use super::culture::Culture;
 use super::expression::ModelExpression;
+use super::perspective::Perspective;
+use super::scrub::{self, ScrubPolicy, REDACTED};
 use super::skip_if::{false_, is_false};
 use super::table::Table;
 use super::traits::RecursiveSort;
+use super::unknown::UnknownFields;
 use super::{datasource::DataSource, relationship::Relationship, roles::Role};
 
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct Model {
     pub culture: String,
 
@@ -37,10 +41,126 @@ pub struct Model {
     pub tables: Vec<Table>,
     pub relationships: Vec<Relationship>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub perspectives: Option<Vec<Perspective>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cultures: Option<Vec<Culture>>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub roles: Option<Vec<Role>>,
     pub expressions: Vec<ModelExpression>,
     pub annotations: Vec<Annotation>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl Model {
+    /// Lists relationships and hierarchy levels that refer to tables or
+    /// columns which do not exist. Names are matched case-insensitively, as
+    /// Analysis Services does.
+    #[must_use]
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems: Vec<String> = self.tables.iter().flat_map(Table::validate).collect();
+
+        for relationship in &self.relationships {
+            let ends = [
+                (&relationship.from_table, &relationship.from_column),
+                (&relationship.to_table, &relationship.to_column),
+            ];
+            for (table, column) in ends {
+                let table_name = table.to_lowercase();
+                match self
+                    .tables
+                    .iter()
+                    .find(|t| t.name.to_lowercase() == table_name)
+                {
+                    None => problems.push(format!(
+                        "relationship {:?} refers to unknown table {table:?}",
+                        relationship.name
+                    )),
+                    Some(t) if !t.has_column(column) => {
+                        problems.push(format!(
+                            "relationship {:?} refers to unknown column {table:?}[{column}]",
+                            relationship.name
+                        ));
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        problems
+    }
+
+    /// Removes the passwords, keys and tokens of data sources, and replaces
+    /// usernames and addresses with [`REDACTED`](super::scrub::REDACTED) as
+    /// `policy` asks.
+    ///
+    /// Redacted addresses are also redacted from data source names and from
+    /// the string literals of M expressions and partition queries, and the
+    /// references to renamed data sources updated to match.
+    pub fn scrub(&mut self, policy: ScrubPolicy) {
+        let mut addresses = Vec::new();
+        for data_source in &mut self.data_sources {
+            addresses.extend(data_source.scrub(policy));
+        }
+        addresses.sort();
+        addresses.dedup();
+        addresses.retain(|address| !address.is_empty() && address != REDACTED);
+
+        if !addresses.is_empty() {
+            self.scrub_references(&addresses);
+        }
+    }
+
+    fn scrub_references(&mut self, addresses: &[String]) {
+        let mut replacements = Vec::new();
+        let mut names: Vec<String> = Vec::new();
+        for data_source in &mut self.data_sources {
+            let name = scrub::redact_text(data_source.name(), addresses);
+            let mut unique = name.clone();
+            let mut n = 1;
+            while names.contains(&unique.to_lowercase()) {
+                n += 1;
+                unique = format!("{name} {n}");
+            }
+            names.push(unique.to_lowercase());
+
+            if unique != data_source.name() {
+                replacements.push((data_source.name().to_string(), unique.clone()));
+                data_source.rename(unique);
+            }
+        }
+        replacements.extend(
+            addresses
+                .iter()
+                .map(|address| (address.clone(), REDACTED.to_string())),
+        );
+
+        for expression in &mut self.expressions {
+            expression.replace_literals(&replacements);
+        }
+        for table in &mut self.tables {
+            for partition in &mut table.partitions {
+                partition.source.replace_literals(&replacements);
+            }
+        }
+    }
+
+    /// Lists the secrets held in plain text, e.g.
+    /// `data source "SQL/localhost;Sales": credential Password`.
+    #[must_use]
+    pub fn secrets(&self) -> Vec<String> {
+        self.data_sources
+            .iter()
+            .flat_map(|d| {
+                d.secrets()
+                    .into_iter()
+                    .map(move |secret| format!("data source {:?}: {secret}", d.name()))
+            })
+            .collect()
+    }
 }
 
 impl RecursiveSort for Model {
@@ -48,6 +168,12 @@ impl RecursiveSort for Model {
         self.data_sources.sort();
         self.tables.recursive_sort();
         self.relationships.sort();
+        if let Some(perspectives) = &mut self.perspectives {
+            perspectives.recursive_sort();
+        }
+        if let Some(cultures) = &mut self.cultures {
+            cultures.recursive_sort();
+        }
         {
             if let Some(roles) = &mut self.roles {
                 roles.recursive_sort();
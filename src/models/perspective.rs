@@ -0,0 +1,286 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::annotations::Annotation;
+use crate::models::traits::RecursiveSort;
+use crate::models::unknown::UnknownFields;
+
+/// A named subset of the model's tables, columns, measures and hierarchies.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Perspective {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    pub tables: Vec<PerspectiveTable>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Vec<Annotation>>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl RecursiveSort for Perspective {
+    fn recursive_sort(&mut self) {
+        self.tables.recursive_sort();
+        if let Some(a) = &mut self.annotations {
+            a.sort();
+        }
+    }
+}
+
+impl Ord for Perspective {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name.to_lowercase().cmp(&other.name.to_lowercase())
+    }
+}
+
+impl PartialOrd for Perspective {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A table included in a perspective, with the members of it included.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PerspectiveTable {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_all: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<PerspectiveColumn>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub measures: Option<Vec<PerspectiveMeasure>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hierarchies: Option<Vec<PerspectiveHierarchy>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Vec<Annotation>>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl RecursiveSort for PerspectiveTable {
+    fn recursive_sort(&mut self) {
+        if let Some(c) = &mut self.columns {
+            c.recursive_sort();
+        }
+        if let Some(m) = &mut self.measures {
+            m.recursive_sort();
+        }
+        if let Some(h) = &mut self.hierarchies {
+            h.recursive_sort();
+        }
+        if let Some(a) = &mut self.annotations {
+            a.sort();
+        }
+    }
+}
+
+impl Ord for PerspectiveTable {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name.to_lowercase().cmp(&other.name.to_lowercase())
+    }
+}
+
+impl PartialOrd for PerspectiveTable {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PerspectiveColumn {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Vec<Annotation>>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl RecursiveSort for PerspectiveColumn {
+    fn recursive_sort(&mut self) {
+        if let Some(a) = &mut self.annotations {
+            a.sort();
+        }
+    }
+}
+
+impl Ord for PerspectiveColumn {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name.to_lowercase().cmp(&other.name.to_lowercase())
+    }
+}
+
+impl PartialOrd for PerspectiveColumn {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PerspectiveMeasure {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Vec<Annotation>>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl RecursiveSort for PerspectiveMeasure {
+    fn recursive_sort(&mut self) {
+        if let Some(a) = &mut self.annotations {
+            a.sort();
+        }
+    }
+}
+
+impl Ord for PerspectiveMeasure {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name.to_lowercase().cmp(&other.name.to_lowercase())
+    }
+}
+
+impl PartialOrd for PerspectiveMeasure {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PerspectiveHierarchy {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Vec<Annotation>>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl RecursiveSort for PerspectiveHierarchy {
+    fn recursive_sort(&mut self) {
+        if let Some(a) = &mut self.annotations {
+            a.sort();
+        }
+    }
+}
+
+impl Ord for PerspectiveHierarchy {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name.to_lowercase().cmp(&other.name.to_lowercase())
+    }
+}
+
+impl PartialOrd for PerspectiveHierarchy {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::models::test::{there_and_back_test, FromValue};
+
+    #[test]
+    fn can_read_and_write_perspective() {
+        let input = json!(
+            {
+                "name": "Sales",
+                "description": "Just the sales tables",
+                "tables": [
+                    {
+                        "name": "Calendar",
+                        "includeAll": true
+                    },
+                    {
+                        "name": "Sales",
+                        "columns": [{"name": "Amount"}],
+                        "measures": [
+                            {
+                                "name": "Total Sales",
+                                "annotations": [{"name": "Owner", "value": "Finance"}]
+                            }
+                        ],
+                        "hierarchies": [{"name": "Product Hierarchy"}]
+                    }
+                ],
+                "annotations": [{"name": "Audience", "value": "Sales team"}]
+            }
+        );
+
+        there_and_back_test(&input, Perspective::from_value);
+    }
+
+    #[test]
+    fn perspective_members_are_sorted_by_name() {
+        let mut perspective = Perspective::from_value(&json!(
+            {
+                "name": "Sales",
+                "tables": [
+                    {
+                        "name": "sales",
+                        "columns": [{"name": "Quantity"}, {"name": "amount"}],
+                        "measures": [{"name": "Total"}, {"name": "Average"}],
+                        "hierarchies": [{"name": "Product"}, {"name": "Geography"}]
+                    },
+                    {"name": "Calendar", "includeAll": true}
+                ]
+            }
+        ));
+        let expected = json!(
+            {
+                "name": "Sales",
+                "tables": [
+                    {"name": "Calendar", "includeAll": true},
+                    {
+                        "name": "sales",
+                        "columns": [{"name": "amount"}, {"name": "Quantity"}],
+                        "measures": [{"name": "Average"}, {"name": "Total"}],
+                        "hierarchies": [{"name": "Geography"}, {"name": "Product"}]
+                    }
+                ]
+            }
+        );
+
+        perspective.recursive_sort();
+
+        assert_eq!(serde_json::to_value(&perspective).unwrap(), expected);
+    }
+}
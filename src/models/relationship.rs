@@ -19,9 +19,10 @@ along with this program.  If not, see <https://www.gnu.org/licenses/>.
 use serde::{Deserialize, Serialize};
 
 use super::skip_if::{is_true, true_};
+use super::unknown::UnknownFields;
 
 #[derive(Deserialize, Debug, Serialize, PartialEq, Eq)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct Relationship {
     pub name: String,
     pub from_table: String,
@@ -37,6 +38,9 @@ pub struct Relationship {
         skip_serializing_if = "CrossFilterBehaviour::is_single"
     )]
     cross_filter_behaviour: CrossFilterBehaviour,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
 }
 
 impl Ord for Relationship {
@@ -16,26 +16,49 @@ You should have received a copy of the GNU General Public License
 along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-pub use member::Member;
+pub use member::{ExternalMember, Member, WindowsMember};
+pub use permission::{ColumnPermission, TablePermission};
 pub use role::Role;
 
 mod role {
+    use crate::models::annotations::Annotation;
     use crate::models::traits::RecursiveSort;
+    use crate::models::unknown::UnknownFields;
 
     use super::member::Member;
+    use super::permission::TablePermission;
     use serde::{Deserialize, Serialize};
 
     #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-    #[serde(rename_all = "camelCase", deny_unknown_fields)]
+    #[serde(rename_all = "camelCase")]
     pub struct Role {
         pub name: String,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub description: Option<String>,
+
         pub model_permission: String,
         pub members: Vec<Member>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub table_permissions: Option<Vec<TablePermission>>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub annotations: Option<Vec<Annotation>>,
+
+        #[serde(flatten)]
+        pub unknown: UnknownFields,
     }
 
     impl RecursiveSort for Role {
         fn recursive_sort(&mut self) {
             self.members.sort();
+            if let Some(t) = &mut self.table_permissions {
+                t.recursive_sort();
+            }
+            if let Some(a) = &mut self.annotations {
+                a.sort();
+            }
         }
     }
 
@@ -53,23 +76,54 @@ mod role {
 }
 
 mod member {
-    use serde::{Deserialize, Serialize};
+    use crate::models::unknown::UnknownFields;
+    use crate::models::untagged::{deserialize_untagged, variant};
+    use serde::{Deserialize, Deserializer, Serialize};
 
-    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-    #[serde(rename_all = "camelCase", deny_unknown_fields)]
-    pub struct Member {
-        #[serde(rename = "memberName")]
-        pub name: String,
+    /// A user or group in a role.
+    #[derive(Serialize, Debug, PartialEq, Eq)]
+    #[serde(untagged)]
+    pub enum Member {
+        /// An Azure AD user or group, as used by Azure Analysis Services and
+        /// Power BI.
+        External(ExternalMember),
 
-        #[serde(rename = "memberId")]
-        pub id: String,
+        /// A Windows user or group, as used by Analysis Services on premises.
+        Windows(WindowsMember),
+    }
 
-        pub identity_provider: String,
+    impl<'de> Deserialize<'de> for Member {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            // `External` must be tried first, as `Windows` would otherwise
+            // accept its `identityProvider` as an unknown field when
+            // preserving them.
+            deserialize_untagged(
+                deserializer,
+                "Member",
+                &[
+                    ("External", |v| variant(v).map(Self::External)),
+                    ("Windows", |v| variant(v).map(Self::Windows)),
+                ],
+            )
+        }
+    }
+
+    impl Member {
+        #[must_use]
+        pub fn name(&self) -> &str {
+            match self {
+                Self::External(m) => &m.name,
+                Self::Windows(m) => &m.name,
+            }
+        }
     }
 
     impl Ord for Member {
         fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-            self.name.to_lowercase().cmp(&other.name.to_lowercase())
+            self.name().to_lowercase().cmp(&other.name().to_lowercase())
         }
     }
 
@@ -78,10 +132,49 @@ mod member {
             Some(self.cmp(other))
         }
     }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ExternalMember {
+        #[serde(rename = "memberName")]
+        pub name: String,
+
+        #[serde(rename = "memberId", skip_serializing_if = "Option::is_none")]
+        pub id: Option<String>,
+
+        pub identity_provider: String,
+
+        /// Whether the member is a `user` or `group`, or `auto` to look it up.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub member_type: Option<String>,
+
+        #[serde(flatten)]
+        pub unknown: UnknownFields,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct WindowsMember {
+        /// The account name, e.g. `DOMAIN\user`.
+        #[serde(rename = "memberName")]
+        pub name: String,
+
+        #[serde(rename = "memberSid", skip_serializing_if = "Option::is_none")]
+        pub sid: Option<String>,
+
+        #[serde(rename = "memberId", skip_serializing_if = "Option::is_none")]
+        pub id: Option<String>,
+
+        #[serde(flatten)]
+        pub unknown: UnknownFields,
+    }
+
     #[cfg(test)]
     mod test {
         use super::Member;
+        use crate::models::test::{there_and_back_test, FromValue};
         use serde_json;
+        use serde_json::json;
 
         #[test]
         fn can_create_member_from_string() {
@@ -95,9 +188,238 @@ mod member {
 
             let member: Member = serde_json::from_str(input).unwrap();
 
-            assert_eq!(member.id, "user.name2@username.com".to_string());
+            let Member::External(member) = member else {
+                panic!("expected an external member, got {member:?}");
+            };
+            assert_eq!(member.id.as_deref(), Some("user.name2@username.com"));
             assert_eq!(member.name, "user.name@username.com".to_string());
             assert_eq!(member.identity_provider, "AzureAD".to_string());
         }
+
+        #[test]
+        fn readwrite_windows_member() {
+            let input = json!(
+                {
+                    "memberName": "CONTOSO\\Sales Team",
+                    "memberSid": "S-1-5-21-1004336348-1177238915-682003330-512"
+                }
+            );
+
+            there_and_back_test(&input, Member::from_value);
+            assert!(matches!(Member::from_value(&input), Member::Windows(_)));
+        }
+
+        #[test]
+        fn readwrite_external_group_member() {
+            let input = json!(
+                {
+                    "memberName": "Sales Team",
+                    "memberId": "obj:4ad8d2a4-4b0b-4c8b-9fa6-54f2ec70c3f6@72f988bf-86f1-41af-91ab-2d7cd011db47",
+                    "identityProvider": "AzureAD",
+                    "memberType": "group"
+                }
+            );
+
+            there_and_back_test(&input, Member::from_value);
+        }
+
+        #[test]
+        fn members_of_each_kind_sort_by_name_ignoring_case() {
+            let mut members = [
+                Member::from_value(
+                    &json!({"memberName": "zoe@contoso.com", "identityProvider": "AzureAD"}),
+                ),
+                Member::from_value(&json!({"memberName": "CONTOSO\\Admins"})),
+                Member::from_value(
+                    &json!({"memberName": "alice@contoso.com", "identityProvider": "AzureAD"}),
+                ),
+            ];
+
+            members.sort();
+
+            let names: Vec<_> = members.iter().map(Member::name).collect();
+            assert_eq!(
+                names,
+                vec!["alice@contoso.com", "CONTOSO\\Admins", "zoe@contoso.com"]
+            );
+        }
+    }
+}
+
+mod permission {
+    use crate::models::annotations::Annotation;
+    use crate::models::expression::{Expression, Expressive};
+    use crate::models::traits::RecursiveSort;
+    use crate::models::unknown::UnknownFields;
+    use serde::{Deserialize, Serialize};
+
+    /// The row and object level security a role applies to a table.
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TablePermission {
+        /// The name of the table.
+        pub name: String,
+
+        /// The DAX filter limiting the rows members can see.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        filter_expression: Option<Expression>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub metadata_permission: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub column_permissions: Option<Vec<ColumnPermission>>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub annotations: Option<Vec<Annotation>>,
+
+        #[serde(flatten)]
+        pub unknown: UnknownFields,
+    }
+
+    impl Expressive for TablePermission {
+        fn expression(&self) -> Option<String> {
+            self.filter_expression.as_ref().map(Expression::to_string)
+        }
+    }
+
+    impl RecursiveSort for TablePermission {
+        fn recursive_sort(&mut self) {
+            if let Some(c) = &mut self.column_permissions {
+                c.sort();
+            }
+            if let Some(a) = &mut self.annotations {
+                a.sort();
+            }
+        }
+    }
+
+    impl Ord for TablePermission {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.name.to_lowercase().cmp(&other.name.to_lowercase())
+        }
+    }
+
+    impl PartialOrd for TablePermission {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    /// The object level security a role applies to a column.
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ColumnPermission {
+        /// The name of the column.
+        pub name: String,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub metadata_permission: Option<String>,
+
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub annotations: Option<Vec<Annotation>>,
+
+        #[serde(flatten)]
+        pub unknown: UnknownFields,
+    }
+
+    impl Ord for ColumnPermission {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.name.to_lowercase().cmp(&other.name.to_lowercase())
+        }
+    }
+
+    impl PartialOrd for ColumnPermission {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use serde_json::json;
+
+        use super::TablePermission;
+        use crate::models::expression::Expressive;
+        use crate::models::roles::Role;
+        use crate::models::test::{there_and_back_test, FromValue};
+        use crate::models::traits::RecursiveSort;
+
+        #[test]
+        fn can_read_and_write_role_with_permissions() {
+            let input = json!(
+                {
+                    "name": "West Region",
+                    "description": "Sales staff in the west",
+                    "modelPermission": "read",
+                    "members": [],
+                    "tablePermissions": [
+                        {
+                            "name": "Sales",
+                            "filterExpression": "[Region] = \"West\"",
+                            "columnPermissions": [
+                                {"name": "Cost", "metadataPermission": "none"}
+                            ]
+                        },
+                        {
+                            "name": "Employees",
+                            "metadataPermission": "none"
+                        }
+                    ],
+                    "annotations": [{"name": "Owner", "value": "HR"}]
+                }
+            );
+
+            there_and_back_test(&input, Role::from_value);
+        }
+
+        #[test]
+        fn filter_expression_is_expressive() {
+            let permission = TablePermission::from_value(&json!(
+                {
+                    "name": "Sales",
+                    "filterExpression": ["[Region] = \"West\"", "    || [Region] = \"North\""]
+                }
+            ));
+
+            assert_eq!(
+                permission.expression().as_deref(),
+                Some("[Region] = \"West\"\n    || [Region] = \"North\"")
+            );
+        }
+
+        #[test]
+        fn permissions_are_sorted_by_name() {
+            let mut role = Role::from_value(&json!(
+                {
+                    "name": "Restricted",
+                    "modelPermission": "read",
+                    "members": [],
+                    "tablePermissions": [
+                        {
+                            "name": "sales",
+                            "columnPermissions": [
+                                {"name": "Margin", "metadataPermission": "none"},
+                                {"name": "cost", "metadataPermission": "none"}
+                            ]
+                        },
+                        {"name": "Employees", "metadataPermission": "none"}
+                    ]
+                }
+            ));
+
+            role.recursive_sort();
+
+            let permissions = role.table_permissions.as_ref().unwrap();
+            let tables: Vec<_> = permissions.iter().map(|t| t.name.as_str()).collect();
+            let columns: Vec<_> = permissions[1]
+                .column_permissions
+                .iter()
+                .flatten()
+                .map(|c| c.name.as_str())
+                .collect();
+            assert_eq!(tables, vec!["Employees", "sales"]);
+            assert_eq!(columns, vec!["cost", "Margin"]);
+        }
     }
 }
@@ -0,0 +1,171 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+/// Written in place of usernames, and of addresses when they are scrubbed.
+pub const REDACTED: &str = "<redacted>";
+
+/// What [`Model::scrub`](super::Model::scrub) removes besides passwords, keys
+/// and tokens, which are always removed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ScrubPolicy {
+    /// Replace usernames and account names.
+    pub usernames: bool,
+
+    /// Replace server, database, url and path addresses, including the
+    /// paths of credentials.
+    pub addresses: bool,
+}
+
+/// Whether a credential property or connection string key holds a secret,
+/// e.g. `Password`, `pwd`, `Key`, `AccessToken` or `ClientSecret`.
+pub(crate) fn is_secret(name: &str) -> bool {
+    let name = name.to_lowercase();
+    ["password", "secret", "token"]
+        .iter()
+        .any(|s| name.contains(s))
+        || matches!(name.as_str(), "pwd" | "key" | "accountkey")
+}
+
+/// Whether a connection string key or ODBC option holds a username, e.g.
+/// `User ID` or `uid`.
+pub(crate) fn is_username(key: &str) -> bool {
+    matches!(
+        key.to_lowercase().as_str(),
+        "user id" | "userid" | "uid" | "user" | "username"
+    )
+}
+
+fn is_address(key: &str) -> bool {
+    matches!(
+        key,
+        "data source"
+            | "server"
+            | "address"
+            | "addr"
+            | "network address"
+            | "host"
+            | "location"
+            | "initial catalog"
+            | "database"
+            | "dsn"
+    )
+}
+
+/// Scrubs the `key=value` pairs of a connection string, dropping secrets and
+/// adding any addresses redacted to `redacted`. Values are assumed not to
+/// contain `;`.
+pub(crate) fn scrub_connection_string(
+    connection_string: &str,
+    policy: ScrubPolicy,
+    redacted: &mut Vec<String>,
+) -> String {
+    connection_string
+        .split(';')
+        .filter_map(|pair| {
+            let Some((key, value)) = pair.split_once('=') else {
+                return Some(pair.to_string());
+            };
+            let name = key.trim().to_lowercase();
+            if is_secret(&name) {
+                None
+            } else if policy.addresses && is_address(&name) {
+                redacted.push(value.trim().to_string());
+                Some(format!("{key}={REDACTED}"))
+            } else if policy.usernames && is_username(&name) {
+                Some(format!("{key}={REDACTED}"))
+            } else {
+                Some(pair.to_string())
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Replaces each of `addresses` found in `text`, longest first so that an
+/// address containing another is replaced whole.
+pub(crate) fn redact_text(text: &str, addresses: &[String]) -> String {
+    let mut addresses: Vec<&String> = addresses.iter().filter(|a| !a.is_empty()).collect();
+    addresses.sort_by_key(|a| std::cmp::Reverse(a.len()));
+
+    addresses
+        .into_iter()
+        .fold(text.to_string(), |text, address| {
+            text.replace(address, REDACTED)
+        })
+}
+
+/// Replaces the string literals `"from"` in M or SQL text with `"to"`, e.g.
+/// the `"prod-sql"` in `Sql.Database("prod-sql", "Sales")`.
+pub(crate) fn replace_literals(text: &str, replacements: &[(String, String)]) -> String {
+    replacements
+        .iter()
+        .fold(text.to_string(), |text, (from, to)| {
+            text.replace(&format!("\"{from}\""), &format!("\"{to}\""))
+        })
+}
+
+/// The keys of a connection string that hold secrets.
+pub(crate) fn connection_string_secrets(connection_string: &str) -> Vec<String> {
+    connection_string
+        .split(';')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, _)| key.trim())
+        .filter(|key| is_secret(key))
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn secrets_are_recognised_by_name() {
+        for name in ["Password", "pwd", "Key", "AccessToken", "ClientSecret"] {
+            assert!(is_secret(name), "{name}");
+        }
+        for name in ["Username", "kind", "path", "KeyVault"] {
+            assert!(!is_secret(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn connection_strings_lose_secrets_and_optionally_addresses() {
+        let input = "Provider=SQLNCLI11;Data Source=prod-sql;User ID=loader;Password=hunter2";
+
+        let mut redacted = Vec::new();
+        assert_eq!(
+            scrub_connection_string(input, ScrubPolicy::default(), &mut redacted),
+            "Provider=SQLNCLI11;Data Source=prod-sql;User ID=loader"
+        );
+        assert!(redacted.is_empty());
+        assert_eq!(
+            scrub_connection_string(
+                input,
+                ScrubPolicy {
+                    usernames: true,
+                    addresses: true
+                },
+                &mut redacted
+            ),
+            "Provider=SQLNCLI11;Data Source=<redacted>;User ID=<redacted>"
+        );
+        assert_eq!(redacted, ["prod-sql"]);
+        assert_eq!(connection_string_secrets(input), ["Password"]);
+    }
+}
@@ -19,9 +19,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::models::expression::Expression;
 use crate::models::traits::RecursiveSort;
+use crate::models::unknown::UnknownFields;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub(crate) struct CalculationItem {
     name: String,
 
@@ -33,6 +34,9 @@ pub(crate) struct CalculationItem {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     format_string_definition: Option<FormatStringDefinition>,
+
+    #[serde(flatten)]
+    unknown: UnknownFields,
 }
 
 impl PartialOrd for CalculationItem {
@@ -71,14 +75,20 @@ impl Ord for CalculationItem {
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 struct FormatStringDefinition {
     expression: Expression,
+
+    #[serde(flatten)]
+    unknown: UnknownFields,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub(crate) struct CalculationGroup {
     #[serde(skip_serializing_if = "Option::is_none")]
     precedence: Option<isize>,
     calculation_items: Vec<CalculationItem>,
+
+    #[serde(flatten)]
+    unknown: UnknownFields,
 }
 
 impl RecursiveSort for CalculationGroup {
@@ -19,14 +19,38 @@ along with this program.  If not, see <https://www.gnu.org/licenses/>.
 use crate::models::annotations::Annotation;
 use crate::models::expression::{Expression, Expressive};
 use crate::models::traits::RecursiveSort;
-use serde::{Deserialize, Serialize};
+use crate::models::unknown::{is_preserving_unknown, UnknownFields};
+use crate::models::untagged::{deserialize_untagged, variant};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(untagged, rename_all = "camelCase", deny_unknown_fields)]
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(untagged, rename_all = "camelCase")]
 pub enum Column {
     Calculated(Calculated),
-    Sourced(Sourced),
     CalculatedTableColumn(CalculatedTableColumn),
+    Sourced(Sourced),
+}
+
+impl<'de> Deserialize<'de> for Column {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // `CalculatedTableColumn` must be tried before `Sourced`, which would
+        // otherwise accept its `type` as an unknown field when preserving them.
+        deserialize_untagged(
+            deserializer,
+            "Column",
+            &[
+                ("Calculated", |v| variant(v).map(Self::Calculated)),
+                ("CalculatedTableColumn", |v| {
+                    variant(v).map(Self::CalculatedTableColumn)
+                }),
+                ("Sourced", |v| variant(v).map(Self::Sourced)),
+            ],
+        )
+    }
 }
 
 impl PartialOrd for Column {
@@ -49,7 +73,7 @@ impl Attributes for Column {
             Self::CalculatedTableColumn(c) => c.name(),
         }
     }
-    fn data_type(&self) -> String {
+    fn data_type(&self) -> DataType {
         match self {
             Self::Calculated(c) => c.data_type(),
             Self::Sourced(c) => c.data_type(),
@@ -88,7 +112,7 @@ impl RecursiveSort for Column {
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 struct CommonColumn {
     name: String,
-    data_type: String,
+    data_type: DataType,
 
     #[serde(skip_serializing_if = "DataCategory::is_uncategorized", default)]
     data_category: DataCategory,
@@ -97,9 +121,98 @@ struct CommonColumn {
     is_hidden: Option<bool>,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+/// The type of the values in a column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataType {
+    String,
+    Int64,
+    Double,
+    Decimal,
+    DateTime,
+    Boolean,
+    Binary,
+    Variant,
+    Unknown,
+    Automatic,
+
+    /// A type this version does not recognise, only accepted when preserving
+    /// unknown fields and written back unchanged.
+    Unrecognised(String),
+}
+
+impl DataType {
+    const KNOWN: [Self; 10] = [
+        Self::String,
+        Self::Int64,
+        Self::Double,
+        Self::Decimal,
+        Self::DateTime,
+        Self::Boolean,
+        Self::Binary,
+        Self::Variant,
+        Self::Unknown,
+        Self::Automatic,
+    ];
+
+    /// The name used in bim files, e.g. `dateTime`.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::String => "string",
+            Self::Int64 => "int64",
+            Self::Double => "double",
+            Self::Decimal => "decimal",
+            Self::DateTime => "dateTime",
+            Self::Boolean => "boolean",
+            Self::Binary => "binary",
+            Self::Variant => "variant",
+            Self::Unknown => "unknown",
+            Self::Automatic => "automatic",
+            Self::Unrecognised(name) => name,
+        }
+    }
+}
+
+impl std::fmt::Display for DataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for DataType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for DataType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+
+        if let Some(known) = Self::KNOWN.into_iter().find(|t| t.as_str() == name) {
+            return Ok(known);
+        }
+        if is_preserving_unknown() {
+            return Ok(Self::Unrecognised(name));
+        }
+        let expected: Vec<_> = Self::KNOWN.iter().map(|t| format!("`{t}`")).collect();
+        Err(D::Error::custom(format!(
+            "unknown data type `{name}`, expected one of {}",
+            expected.join(", ")
+        )))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
 #[serde(deny_unknown_fields)]
 enum DataCategory {
+    #[default]
     Uncategorized,
     Address,
     City,
@@ -117,21 +230,16 @@ enum DataCategory {
     WebUrl,
 }
 
-impl Default for DataCategory {
-    fn default() -> Self {
-        Self::Uncategorized
-    }
-}
-
 impl DataCategory {
     fn is_uncategorized(&self) -> bool {
         self.eq(&Self::Uncategorized)
     }
 }
 
+#[allow(dead_code)]
 pub trait Attributes {
     fn name(&self) -> String;
-    fn data_type(&self) -> String;
+    fn data_type(&self) -> DataType;
     fn is_hidden(&self) -> bool;
 }
 
@@ -139,7 +247,7 @@ impl Attributes for CommonColumn {
     fn name(&self) -> String {
         self.name.clone()
     }
-    fn data_type(&self) -> String {
+    fn data_type(&self) -> DataType {
         self.data_type.clone()
     }
     fn is_hidden(&self) -> bool {
@@ -148,7 +256,7 @@ impl Attributes for CommonColumn {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct Calculated {
     #[serde(flatten)]
     common: CommonColumn,
@@ -163,6 +271,9 @@ pub struct Calculated {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub format_string: Option<String>,
     pub display_folder: Option<String>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
 }
 
 impl Expressive for Calculated {
@@ -175,7 +286,7 @@ impl Attributes for Calculated {
     fn name(&self) -> String {
         self.common.name()
     }
-    fn data_type(&self) -> String {
+    fn data_type(&self) -> DataType {
         self.common.data_type()
     }
     fn is_hidden(&self) -> bool {
@@ -184,7 +295,7 @@ impl Attributes for Calculated {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct CalculatedTableColumn {
     #[serde(flatten)]
     common: CommonColumn,
@@ -200,13 +311,16 @@ pub struct CalculatedTableColumn {
     pub format_string: Option<String>,
 
     source_column: String,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
 }
 
 impl Attributes for CalculatedTableColumn {
     fn name(&self) -> String {
         self.common.name()
     }
-    fn data_type(&self) -> String {
+    fn data_type(&self) -> DataType {
         self.common.data_type()
     }
     fn is_hidden(&self) -> bool {
@@ -215,7 +329,7 @@ impl Attributes for CalculatedTableColumn {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct Sourced {
     #[serde(flatten)]
     common: CommonColumn,
@@ -232,10 +346,13 @@ pub struct Sourced {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sort_by_column: Option<String>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
 }
 
 impl Attributes for Sourced {
-    fn data_type(&self) -> String {
+    fn data_type(&self) -> DataType {
         self.common.data_type()
     }
     fn is_hidden(&self) -> bool {
@@ -251,6 +368,7 @@ mod tests {
     use crate::models::test::there_and_back_test;
     use crate::models::test::FromValue;
 
+    use super::Attributes;
     use super::Column;
     use super::Expressive;
     use serde_json;
@@ -297,14 +415,16 @@ mod tests {
     use super::Calculated;
     use super::CommonColumn;
     use super::DataCategory;
+    use super::DataType;
     use super::Sourced;
+    use super::UnknownFields;
 
     impl Column {
-        fn new_calculated(name: &str, data_type: &str, expression: &str) -> Self {
+        fn new_calculated(name: &str, data_type: &DataType, expression: &str) -> Self {
             Self::Calculated(Calculated {
                 common: CommonColumn {
                     name: name.to_string(),
-                    data_type: data_type.to_string(),
+                    data_type: data_type.clone(),
                     is_hidden: None,
                     data_category: DataCategory::default(),
                 },
@@ -313,18 +433,19 @@ mod tests {
                 is_data_type_inferred: None,
                 format_string: None,
                 display_folder: None,
+                unknown: UnknownFields::default(),
             })
         }
         fn new_sourced(
             name: &str,
-            data_type: &str,
+            data_type: &DataType,
             source_column: &str,
             sort_by_column: &str,
         ) -> Self {
             Self::Sourced(Sourced {
                 common: CommonColumn {
                     name: name.to_string(),
-                    data_type: data_type.to_string(),
+                    data_type: data_type.clone(),
                     is_hidden: None,
                     data_category: DataCategory::default(),
                 },
@@ -333,6 +454,7 @@ mod tests {
                 description: None,
                 format_string: None,
                 annotations: None,
+                unknown: UnknownFields::default(),
             })
         }
     }
@@ -340,16 +462,44 @@ mod tests {
     #[test]
     fn test_can_sort_columns() {
         let mut columns = vec![
-            Column::new_calculated("ZZZ Calculated", "int64", "COUNTROWS(Calculations)"),
-            Column::new_sourced("ZZZ Sourced", "int64", "ZZZ Sourced", "ZZZ Sourced"),
-            Column::new_sourced("AAA Sourced", "int64", "AAA Sourced", "AAA Sourced"),
-            Column::new_calculated("AAA Calculated", "int64", "COUNTROWS(Calculated)"),
+            Column::new_calculated(
+                "ZZZ Calculated",
+                &DataType::Int64,
+                "COUNTROWS(Calculations)",
+            ),
+            Column::new_sourced(
+                "ZZZ Sourced",
+                &DataType::Int64,
+                "ZZZ Sourced",
+                "ZZZ Sourced",
+            ),
+            Column::new_sourced(
+                "AAA Sourced",
+                &DataType::Int64,
+                "AAA Sourced",
+                "AAA Sourced",
+            ),
+            Column::new_calculated("AAA Calculated", &DataType::Int64, "COUNTROWS(Calculated)"),
         ];
         let expected = vec![
-            Column::new_calculated("AAA Calculated", "int64", "COUNTROWS(Calculated)"),
-            Column::new_sourced("AAA Sourced", "int64", "AAA Sourced", "AAA Sourced"),
-            Column::new_calculated("ZZZ Calculated", "int64", "COUNTROWS(Calculations)"),
-            Column::new_sourced("ZZZ Sourced", "int64", "ZZZ Sourced", "ZZZ Sourced"),
+            Column::new_calculated("AAA Calculated", &DataType::Int64, "COUNTROWS(Calculated)"),
+            Column::new_sourced(
+                "AAA Sourced",
+                &DataType::Int64,
+                "AAA Sourced",
+                "AAA Sourced",
+            ),
+            Column::new_calculated(
+                "ZZZ Calculated",
+                &DataType::Int64,
+                "COUNTROWS(Calculations)",
+            ),
+            Column::new_sourced(
+                "ZZZ Sourced",
+                &DataType::Int64,
+                "ZZZ Sourced",
+                "ZZZ Sourced",
+            ),
         ];
 
         columns.sort();
@@ -454,4 +604,74 @@ mod tests {
 
         there_and_back_test(&data, Column::from_value);
     }
+
+    #[test]
+    fn calculated_table_columns_keep_their_variant_when_preserving_unknown() {
+        use crate::models::unknown::preserving_unknown;
+
+        let data = json!(
+            {
+                "type": "calculatedTableColumn",
+                "name": "Courses Booked",
+                "dataType": "int64",
+                "sourceColumn": "[Courses Booked]",
+                "lineageTag": "9b3d2c1e"
+            }
+        );
+
+        preserving_unknown(true, || {
+            let column = Column::from_value(&data);
+            assert!(matches!(column, Column::CalculatedTableColumn(_)));
+            there_and_back_test(&data, Column::from_value);
+        });
+    }
+
+    #[test]
+    fn every_data_type_round_trips() {
+        for name in [
+            "string",
+            "int64",
+            "double",
+            "decimal",
+            "dateTime",
+            "boolean",
+            "binary",
+            "variant",
+            "unknown",
+            "automatic",
+        ] {
+            let input = json!({"name": "Value", "dataType": name, "sourceColumn": "Value"});
+
+            there_and_back_test(&input, Column::from_value);
+        }
+    }
+
+    #[test]
+    fn misspelt_data_types_are_rejected() {
+        let input = json!({"name": "Quantity", "dataType": "int46", "sourceColumn": "Quantity"});
+
+        let error = serde_json::from_value::<DataType>(input["dataType"].clone()).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "unknown data type `int46`, expected one of `string`, `int64`, `double`, `decimal`, \
+             `dateTime`, `boolean`, `binary`, `variant`, `unknown`, `automatic`"
+        );
+        assert!(serde_json::from_value::<Column>(input).is_err());
+    }
+
+    #[test]
+    fn unrecognised_data_types_are_kept_when_preserving_unknown() {
+        use crate::models::unknown::preserving_unknown;
+
+        let input = json!({"name": "Shape", "dataType": "geography", "sourceColumn": "Shape"});
+
+        preserving_unknown(true, || {
+            there_and_back_test(&input, Column::from_value);
+            assert_eq!(
+                Column::from_value(&input).data_type(),
+                DataType::Unrecognised(String::from("geography"))
+            );
+        });
+    }
 }
@@ -0,0 +1,168 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::annotations::Annotation;
+use crate::models::traits::RecursiveSort;
+use crate::models::unknown::UnknownFields;
+
+/// A user hierarchy, e.g. Year > Quarter > Month.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Hierarchy {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_hidden: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_folder: Option<String>,
+
+    pub levels: Vec<Level>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Vec<Annotation>>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl RecursiveSort for Hierarchy {
+    fn recursive_sort(&mut self) {
+        // Level order is the drill down order, so levels are sorted by
+        // ordinal rather than name.
+        self.levels.recursive_sort();
+        if let Some(a) = &mut self.annotations {
+            a.sort();
+        }
+    }
+}
+
+impl Ord for Hierarchy {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.name.to_lowercase().cmp(&other.name.to_lowercase())
+    }
+}
+
+impl PartialOrd for Hierarchy {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Level {
+    pub name: String,
+    pub ordinal: i32,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// The name of the column on the same table this level shows.
+    pub column: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Vec<Annotation>>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl RecursiveSort for Level {
+    fn recursive_sort(&mut self) {
+        if let Some(a) = &mut self.annotations {
+            a.sort();
+        }
+    }
+}
+
+impl Ord for Level {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.ordinal
+            .cmp(&other.ordinal)
+            .then_with(|| self.name.to_lowercase().cmp(&other.name.to_lowercase()))
+    }
+}
+
+impl PartialOrd for Level {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::models::test::{there_and_back_test, FromValue};
+
+    #[test]
+    fn can_read_and_write_hierarchy() {
+        let input = json!(
+            {
+                "name": "Calendar",
+                "displayFolder": "Dates",
+                "levels": [
+                    {"ordinal": 0, "name": "Year", "column": "Year"},
+                    {
+                        "ordinal": 1,
+                        "name": "Month",
+                        "column": "Month Name",
+                        "annotations": [{"name": "Format", "value": "MMM"}]
+                    }
+                ]
+            }
+        );
+
+        there_and_back_test(&input, Hierarchy::from_value);
+    }
+
+    #[test]
+    fn hierarchies_sort_by_name_and_levels_by_ordinal() {
+        let mut hierarchies = vec![
+            Hierarchy::from_value(&json!({"name": "Products", "levels": []})),
+            Hierarchy::from_value(&json!(
+                {
+                    "name": "Calendar",
+                    "levels": [
+                        {"ordinal": 2, "name": "Day", "column": "Date"},
+                        {"ordinal": 0, "name": "Year", "column": "Year"},
+                        {"ordinal": 1, "name": "Month", "column": "Month"}
+                    ]
+                }
+            )),
+        ];
+
+        hierarchies.recursive_sort();
+
+        let names: Vec<_> = hierarchies.iter().map(|h| h.name.as_str()).collect();
+        let levels: Vec<_> = hierarchies[0]
+            .levels
+            .iter()
+            .map(|l| l.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Calendar", "Products"]);
+        assert_eq!(levels, vec!["Year", "Month", "Day"]);
+    }
+}
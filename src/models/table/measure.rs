@@ -20,10 +20,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::models::annotations::Annotation;
 use crate::models::expression::{Expression, Expressive};
+use crate::models::unknown::UnknownFields;
 use crate::models::RecursiveSort;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct Measure {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -41,6 +42,9 @@ pub struct Measure {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     kpi: Option<Kpi>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
 }
 
 impl Ord for Measure {
@@ -70,7 +74,7 @@ impl RecursiveSort for Measure {
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 struct Kpi {
     target_expression: String,
     target_format_string: String,
@@ -79,6 +83,9 @@ struct Kpi {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     annotations: Option<Vec<Annotation>>,
+
+    #[serde(flatten)]
+    unknown: UnknownFields,
 }
 
 #[cfg(test)]
@@ -89,6 +96,7 @@ mod test {
     use super::Kpi;
     use super::Measure;
     use super::RecursiveSort;
+    use super::UnknownFields;
 
     use crate::models::test::{there_and_back_test, FromValue};
 
@@ -103,6 +111,7 @@ mod test {
                 display_folder: None,
                 annotations: None,
                 kpi: None,
+                unknown: UnknownFields::default(),
             }
         }
     }
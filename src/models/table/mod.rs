@@ -1,20 +1,26 @@
 mod calculation_group;
 mod column;
+mod hierarchy;
 mod measure;
 mod partition;
+mod refresh_policy;
 
 use calculation_group::CalculationGroup;
-pub(crate) use column::Column;
+pub use column::DataType;
+pub(crate) use column::{Attributes, Column};
+pub use hierarchy::{Hierarchy, Level};
 pub(crate) use measure::Measure;
 pub(crate) use partition::Partition;
+pub use refresh_policy::RefreshPolicy;
 
 use serde::{Deserialize, Serialize};
 
 use crate::models::skip_if::{false_, is_false};
 use crate::models::traits::RecursiveSort;
+use crate::models::unknown::UnknownFields;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct Table {
     pub(crate) name: String,
 
@@ -26,8 +32,42 @@ pub struct Table {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) measures: Option<Vec<Measure>>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) hierarchies: Option<Vec<Hierarchy>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) refresh_policy: Option<RefreshPolicy>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     calculation_group: Option<CalculationGroup>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl Table {
+    /// Whether the table has a column named `name`, ignoring case.
+    pub(crate) fn has_column(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.columns.iter().any(|c| c.name().to_lowercase() == name)
+    }
+
+    /// Lists hierarchy levels that refer to columns which do not exist.
+    pub(crate) fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        for hierarchy in self.hierarchies.iter().flatten() {
+            for level in &hierarchy.levels {
+                if !self.has_column(&level.column) {
+                    problems.push(format!(
+                        "level {:?} of hierarchy {:?} refers to unknown column {:?}[{}]",
+                        level.name, hierarchy.name, self.name, level.column
+                    ));
+                }
+            }
+        }
+        problems
+    }
 }
 
 impl RecursiveSort for Table {
@@ -37,6 +77,9 @@ impl RecursiveSort for Table {
         if let Some(v) = &mut self.measures {
             v.recursive_sort();
         }
+        if let Some(h) = &mut self.hierarchies {
+            h.recursive_sort();
+        }
 
         if let Some(c) = &mut self.calculation_group {
             c.recursive_sort();
@@ -349,4 +392,53 @@ mod tests {
 
         there_and_back_test(&input, Table::from_value);
     }
+
+    #[test]
+    fn hierarchy_levels_must_refer_to_columns() {
+        let input = json!(
+            {
+                "name": "Calendar",
+                "columns": [{"name": "Year", "dataType": "int64", "sourceColumn": "Year"}],
+                "partitions": [],
+                "hierarchies": [
+                    {
+                        "name": "Dates",
+                        "levels": [
+                            {"ordinal": 0, "name": "Year", "column": "Year"},
+                            {"ordinal": 1, "name": "Month", "column": "Month"}
+                        ]
+                    }
+                ]
+            }
+        );
+
+        let table = Table::from_value(&input);
+
+        assert_eq!(
+            table.validate(),
+            vec![
+                "level \"Month\" of hierarchy \"Dates\" refers to unknown column \"Calendar\"[Month]"
+            ]
+        );
+        there_and_back_test(&input, Table::from_value);
+    }
+
+    #[test]
+    fn hierarchy_levels_match_columns_case_insensitively() {
+        let input = json!(
+            {
+                "name": "Calendar",
+                "columns": [{"name": "Year", "dataType": "int64", "sourceColumn": "Year"}],
+                "partitions": [],
+                "hierarchies": [
+                    {
+                        "name": "Dates",
+                        "levels": [{"ordinal": 0, "name": "Year", "column": "YEAR"}]
+                    }
+                ]
+            }
+        );
+
+        assert!(Table::from_value(&input).validate().is_empty());
+    }
 }
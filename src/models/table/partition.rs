@@ -19,12 +19,15 @@ along with this program.  If not, see <https://www.gnu.org/licenses/>.
 use crate::models::{
     annotations::Annotation,
     expression::{Expression, Expressive},
+    unknown::{is_preserving_unknown, UnknownFields},
     RecursiveSort,
 };
-use serde::{Deserialize, Serialize};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
 pub struct Partition {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mode: Option<String>,
@@ -37,11 +40,22 @@ pub struct Partition {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     annotations: Option<Vec<Annotation>>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
 }
 
 impl Ord for Partition {
+    /// Partitions generated by a refresh policy are ordered chronologically,
+    /// after any others, which are ordered by name.
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.name.to_lowercase().cmp(&other.name.to_lowercase())
+        let (start, other_start) = (self.source.start(), other.source.start());
+
+        start
+            .is_some()
+            .cmp(&other_start.is_some())
+            .then_with(|| start.cmp(&other_start))
+            .then_with(|| self.name.to_lowercase().cmp(&other.name.to_lowercase()))
     }
 }
 
@@ -59,22 +73,202 @@ impl RecursiveSort for Partition {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
-pub struct Source {
-    #[serde(rename = "type")]
-    pub type_: String,
+/// Where a partition's data comes from, tagged by its `type`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase", remote = "Self")]
+pub enum Source {
+    /// A Power Query (M) expression.
+    M(MSource),
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    expression: Option<Expression>,
+    /// A native query against a legacy data source.
+    Query(QuerySource),
+
+    /// A DAX expression defining a calculated table.
+    Calculated(CalculatedSource),
+
+    /// The single partition of a calculation group table.
+    CalculationGroup(CalculationGroupSource),
+
+    /// A table of another model, queried in DirectQuery mode.
+    Entity(EntitySource),
+
+    /// A period of data generated by an incremental refresh policy.
+    PolicyRange(PolicyRangeSource),
+
+    /// A source of a type this version does not recognise, only accepted
+    /// when preserving unknown fields and written back unchanged.
+    #[serde(skip)]
+    Other(Map<String, Value>),
+}
+
+impl Source {
+    const TYPES: [&'static str; 6] = [
+        "m",
+        "query",
+        "calculated",
+        "calculationGroup",
+        "entity",
+        "policyRange",
+    ];
+
+    /// The start of the period a `policyRange` partition holds.
+    #[must_use]
+    pub fn start(&self) -> Option<&str> {
+        match self {
+            Self::PolicyRange(s) => Some(&s.start),
+            _ => None,
+        }
+    }
+}
+
+impl Source {
+    /// Replaces string literals in M and native queries, and renames the
+    /// data sources referred to by name, as `replacements` ask.
+    pub(crate) fn replace_literals(&mut self, replacements: &[(String, String)]) {
+        let rename = |name: &mut String| {
+            if let Some((_, to)) = replacements.iter().find(|(from, _)| from == name) {
+                *name = to.clone();
+            }
+        };
+
+        match self {
+            Self::M(s) => s.expression.replace_literals(replacements),
+            Self::Query(s) => {
+                s.query.replace_literals(replacements);
+                rename(&mut s.data_source);
+            }
+            Self::Entity(s) => s.data_source.iter_mut().for_each(rename),
+            Self::Calculated(_)
+            | Self::CalculationGroup(_)
+            | Self::PolicyRange(_)
+            | Self::Other(_) => {}
+        }
+    }
+}
+
+impl Serialize for Source {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Other(fields) => fields.serialize(serializer),
+            _ => Self::serialize(self, serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Source {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if !is_preserving_unknown() {
+            return Self::deserialize(deserializer);
+        }
+
+        let fields = Map::deserialize(deserializer)?;
+        let type_ = fields.get("type").and_then(Value::as_str);
+        if type_.is_some_and(|t| !Self::TYPES.contains(&t)) {
+            return Ok(Self::Other(fields));
+        }
+        Self::deserialize(Value::Object(fields)).map_err(D::Error::custom)
+    }
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        Self::M(MSource {
+            expression: Expression::String(String::new()),
+            unknown: UnknownFields::default(),
+        })
+    }
 }
 
 impl Expressive for Source {
     fn expression(&self) -> Option<String> {
-        self.expression.as_ref().map(Expression::to_string)
+        match self {
+            Self::M(s) => Some(s.expression.to_string()),
+            Self::Query(s) => Some(s.query.to_string()),
+            Self::Calculated(s) => Some(s.expression.to_string()),
+            Self::CalculationGroup(_) | Self::Entity(_) | Self::PolicyRange(_) | Self::Other(_) => {
+                None
+            }
+        }
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MSource {
+    expression: Expression,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuerySource {
+    /// The name of the data source the query runs against.
+    pub data_source: String,
+    query: Expression,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CalculatedSource {
+    expression: Expression,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CalculationGroupSource {
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EntitySource {
+    pub entity_name: String,
+
+    /// The name of the model expression connecting to the other model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expression_source: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_source: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_name: Option<String>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyRangeSource {
+    /// The start of the period, as an ISO 8601 date time, which sorts
+    /// chronologically.
+    pub start: String,
+    pub end: String,
+    pub granularity: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_bookmark: Option<String>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
 #[cfg(test)]
 mod test {
     use crate::models::{
@@ -83,7 +277,7 @@ mod test {
         traits::RecursiveSort,
     };
 
-    use super::{Expression, Partition, Source};
+    use super::{Expression, Expressive, MSource, Partition, Source, UnknownFields};
 
     impl Partition {
         fn new(name: &str, dataview: &str, source: Source) -> Self {
@@ -93,27 +287,28 @@ mod test {
                 data_view: Some(dataview.to_string()),
                 source,
                 annotations: None,
+                unknown: UnknownFields::default(),
             }
         }
     }
     impl Source {
-        fn new(type_: &str, expression: &str) -> Self {
-            Self {
-                type_: type_.to_string(),
-                expression: Some(Expression::String(expression.to_string())),
-            }
+        fn m(expression: &str) -> Self {
+            Self::M(MSource {
+                expression: Expression::String(expression.to_string()),
+                unknown: UnknownFields::default(),
+            })
         }
     }
 
     #[test]
     fn test_can_sort_partitions() {
         let mut partitions = vec![
-            Partition::new("2022 Onwards", "full", Source::new("m", "Some m script")),
-            Partition::new("2020", "full", Source::new("m", "Some m script")),
+            Partition::new("2022 Onwards", "full", Source::m("Some m script")),
+            Partition::new("2020", "full", Source::m("Some m script")),
         ];
         let expected = vec![
-            Partition::new("2020", "full", Source::new("m", "Some m script")),
-            Partition::new("2022 Onwards", "full", Source::new("m", "Some m script")),
+            Partition::new("2020", "full", Source::m("Some m script")),
+            Partition::new("2022 Onwards", "full", Source::m("Some m script")),
         ];
         partitions.sort();
         assert_eq!(partitions, expected);
@@ -140,17 +335,18 @@ mod test {
         there_and_back_test(&input, Partition::from_value);
     }
 
-    #[test]
-    fn test_partitions_support_sorting_annotations() {
-        impl Annotation {
-            fn new(name: &str, value: &str) -> Self {
-                Self {
-                    name: name.to_owned(),
-                    value: Expression::String(value.to_owned()),
-                }
+    impl Annotation {
+        fn new(name: &str, value: &str) -> Self {
+            Self {
+                name: name.to_owned(),
+                value: Expression::String(value.to_owned()),
+                unknown: UnknownFields::default(),
             }
         }
+    }
 
+    #[test]
+    fn test_partitions_support_sorting_annotations() {
         let mut partition = Partition::default();
         let annotations = vec![
             Annotation::new("ZZZ Annotation", "1"),
@@ -169,4 +365,119 @@ mod test {
 
         assert_eq!(partition.annotations, Some(annotations_sorted));
     }
+
+    fn policy_range(name: &str, start: &str) -> Partition {
+        Partition::from_value(&serde_json::json!(
+            {
+                "mode": "import",
+                "name": name,
+                "source": {
+                    "type": "policyRange",
+                    "start": start,
+                    "end": "2030-01-01T00:00:00",
+                    "granularity": "year"
+                }
+            }
+        ))
+    }
+
+    #[test]
+    fn readwrite_policy_range_partition() {
+        let input = serde_json::json!(
+            {
+                "mode": "import",
+                "name": "2021Q3",
+                "source": {
+                    "type": "policyRange",
+                    "start": "2021-07-01T00:00:00",
+                    "end": "2021-10-01T00:00:00",
+                    "granularity": "quarter",
+                    "refreshBookmark": "2021-09-30T12:00:00"
+                }
+            }
+        );
+
+        there_and_back_test(&input, Partition::from_value);
+    }
+
+    #[test]
+    fn policy_range_partitions_sort_by_start_after_others() {
+        let mut partitions = [
+            policy_range("2021", "2021-01-01T00:00:00"),
+            policy_range("2020Q4", "2020-10-01T00:00:00"),
+            Partition::new("Template", "full", Source::m("Some m script")),
+            policy_range("2019", "2019-01-01T00:00:00"),
+        ];
+
+        partitions.sort();
+
+        let names: Vec<_> = partitions.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Template", "2019", "2020Q4", "2021"]);
+    }
+
+    #[test]
+    fn readwrite_each_source_type() {
+        let sources = [
+            serde_json::json!({"type": "m", "expression": ["let", "    Source = 1", "in", "    Source"]}),
+            serde_json::json!({"type": "query", "dataSource": "SqlServer localhost", "query": "SELECT * FROM Sales"}),
+            serde_json::json!({"type": "calculated", "expression": "CALENDARAUTO()"}),
+            serde_json::json!({"type": "calculationGroup"}),
+            serde_json::json!({"type": "entity", "entityName": "Sales", "expressionSource": "DirectQuery to AS - Sales"}),
+            serde_json::json!({"type": "policyRange", "start": "2021-01-01T00:00:00", "end": "2022-01-01T00:00:00", "granularity": "year"}),
+        ];
+
+        for source in &sources {
+            there_and_back_test(source, Source::from_value);
+        }
+    }
+
+    #[test]
+    fn sources_with_expressions_are_expressive() {
+        let query = Source::from_value(&serde_json::json!(
+            {"type": "query", "dataSource": "SqlServer localhost", "query": "SELECT * FROM Sales"}
+        ));
+        let entity = Source::from_value(&serde_json::json!(
+            {"type": "entity", "entityName": "Sales", "expressionSource": "DirectQuery to AS - Sales"}
+        ));
+
+        assert_eq!(query.expression().as_deref(), Some("SELECT * FROM Sales"));
+        assert_eq!(Source::m("Source").expression().as_deref(), Some("Source"));
+        assert_eq!(entity.expression(), None);
+    }
+
+    #[test]
+    fn unknown_source_types_are_rejected() {
+        let result = serde_json::from_value::<Source>(serde_json::json!({"type": "odbc"}));
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("unknown variant `odbc`"));
+    }
+
+    #[test]
+    fn unknown_source_types_are_kept_when_preserving_unknown() {
+        use crate::models::unknown::preserving_unknown;
+
+        let input = serde_json::json!({"type": "parquet", "location": "sales.parquet"});
+
+        preserving_unknown(true, || {
+            there_and_back_test(&input, Source::from_value);
+            assert!(matches!(Source::from_value(&input), Source::Other(_)));
+        });
+    }
+
+    #[test]
+    fn known_source_types_are_checked_when_preserving_unknown() {
+        use crate::models::unknown::preserving_unknown;
+
+        let result = preserving_unknown(true, || {
+            serde_json::from_value::<Source>(serde_json::json!({"type": "query"}))
+        });
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("missing field `dataSource`"));
+    }
 }
@@ -0,0 +1,105 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::expression::{Expression, Expressive};
+use crate::models::unknown::UnknownFields;
+
+/// An incremental refresh policy, from which the service generates the
+/// table's `policyRange` partitions.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshPolicy {
+    pub policy_type: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+
+    pub rolling_window_granularity: String,
+    pub rolling_window_periods: i32,
+    pub incremental_granularity: String,
+    pub incremental_periods: i32,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub incremental_periods_offset: Option<i32>,
+
+    /// The M query filtered by `RangeStart` and `RangeEnd` to load each
+    /// partition.
+    source_expression: Expression,
+
+    /// The M query whose result decides whether a partition is refreshed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    polling_expression: Option<Expression>,
+
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl RefreshPolicy {
+    #[must_use]
+    pub fn polling_expression(&self) -> Option<String> {
+        self.polling_expression.as_ref().map(Expression::to_string)
+    }
+}
+
+impl Expressive for RefreshPolicy {
+    fn expression(&self) -> Option<String> {
+        Some(self.source_expression.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::models::test::{there_and_back_test, FromValue};
+
+    #[test]
+    fn can_read_and_write_refresh_policy() {
+        let input = json!(
+            {
+                "policyType": "basic",
+                "rollingWindowGranularity": "year",
+                "rollingWindowPeriods": 5,
+                "incrementalGranularity": "day",
+                "incrementalPeriods": 10,
+                "incrementalPeriodsOffset": -1,
+                "sourceExpression": [
+                    "let",
+                    "    Source = Sql.Database(\"server\", \"db\"),",
+                    "    Sales = Source{[Schema=\"dbo\",Item=\"Sales\"]}[Data],",
+                    "    Filtered = Table.SelectRows(Sales, each [Date] >= RangeStart and [Date] < RangeEnd)",
+                    "in",
+                    "    Filtered"
+                ],
+                "pollingExpression": "List.Max(Sales[ModifiedDate])"
+            }
+        );
+
+        there_and_back_test(&input, RefreshPolicy::from_value);
+
+        let policy = RefreshPolicy::from_value(&input);
+        assert!(policy.expression().unwrap().starts_with("let\n"));
+        assert_eq!(
+            policy.polling_expression().as_deref(),
+            Some("List.Max(Sales[ModifiedDate])")
+        );
+    }
+}
@@ -1,3 +1,6 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
 use serde;
 use serde_json;
 pub(crate) trait FromValue {
@@ -28,3 +31,59 @@ where
     let output = item.to_value();
     assert_eq!(input, &output);
 }
+
+/// A bim file wrapping `model`, whose collections default to empty, e.g.
+/// `bim_value(json!({"tables": [...]}))`.
+pub(crate) fn bim_value(model: serde_json::Value) -> serde_json::Value {
+    let mut value = serde_json::json!(
+        {
+            "name": "SemanticModel",
+            "compatibilityLevel": 1500,
+            "model": {
+                "culture": "en-GB",
+                "dataSources": [],
+                "tables": [],
+                "relationships": [],
+                "expressions": [],
+                "annotations": []
+            },
+            "id": "SemanticModel"
+        }
+    );
+    if let serde_json::Value::Object(fields) = model {
+        value["model"]
+            .as_object_mut()
+            .expect("the model is an object")
+            .extend(fields);
+    }
+    value
+}
+
+/// A directory under the system temp directory, removed when dropped.
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("bim_sort_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub(crate) fn touch(&self, name: &str) -> PathBuf {
+        let path = self.0.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "").unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,143 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use std::cell::Cell;
+use std::collections::BTreeMap;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+thread_local! {
+    static PRESERVE_UNKNOWN: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Properties of an object that are not modelled by its struct.
+///
+/// Add as the last field of a struct with `#[serde(flatten)]`. By default
+/// deserializing an object with unknown properties fails, as with
+/// `deny_unknown_fields`. Inside [`preserving_unknown`] they are kept instead
+/// and written back after the known properties, ordered by key. Objects
+/// nested inside them keep the key order they were read with.
+#[derive(Serialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct UnknownFields(BTreeMap<String, Value>);
+
+impl UnknownFields {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.0.keys()
+    }
+
+    pub(crate) fn retain(&mut self, mut f: impl FnMut(&str) -> bool) {
+        self.0.retain(|key, _| f(key));
+    }
+}
+
+impl<'de> Deserialize<'de> for UnknownFields {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fields = BTreeMap::<String, Value>::deserialize(deserializer)?;
+
+        if !is_preserving_unknown() {
+            if let Some(name) = fields.keys().next() {
+                return Err(D::Error::custom(format!("unknown field `{name}`")));
+            }
+        }
+        Ok(Self(fields))
+    }
+}
+
+/// Resets the preserve flag when dropped, even if deserialization panics.
+struct Guard(bool);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        PRESERVE_UNKNOWN.with(|p| p.set(self.0));
+    }
+}
+
+/// Whether unknown properties and values are currently being preserved.
+pub(crate) fn is_preserving_unknown() -> bool {
+    PRESERVE_UNKNOWN.with(Cell::get)
+}
+
+/// Runs `f` with unknown properties either preserved or rejected.
+pub(crate) fn preserving_unknown<T>(preserve: bool, f: impl FnOnce() -> T) -> T {
+    let _guard = Guard(PRESERVE_UNKNOWN.with(|p| p.replace(preserve)));
+    f()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    struct Item {
+        name: String,
+
+        #[serde(flatten)]
+        unknown: UnknownFields,
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected_by_default() {
+        let input = json!({"name": "Item", "lineageTag": "abc"});
+
+        let result = serde_json::from_value::<Item>(input);
+
+        assert!(result.unwrap_err().to_string().contains("`lineageTag`"));
+    }
+
+    #[test]
+    fn unknown_fields_are_written_after_known_fields_in_key_order() {
+        let input = r#"{"zebra":1,"name":"Item","lineageTag":"abc"}"#;
+
+        let item: Item = preserving_unknown(true, || serde_json::from_str(input)).unwrap();
+        let output = serde_json::to_string(&item).unwrap();
+
+        assert_eq!(output, r#"{"name":"Item","lineageTag":"abc","zebra":1}"#);
+    }
+
+    #[test]
+    fn nested_unknown_objects_keep_their_key_order() {
+        let input = r#"{"name":"Item","extendedProperties":[{"type":"json","name":"Layout","value":{"zeta":1,"alpha":2}}]}"#;
+
+        let item: Item = preserving_unknown(true, || serde_json::from_str(input)).unwrap();
+        let output = serde_json::to_string(&item).unwrap();
+
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn preserve_flag_is_restored_afterwards() {
+        let input = json!({"name": "Item", "lineageTag": "abc"});
+
+        preserving_unknown(true, || {
+            serde_json::from_value::<Item>(input.clone()).unwrap();
+        });
+
+        assert!(serde_json::from_value::<Item>(input).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+/*
+Bim Sort, sorts bim files for better compatibility with git
+Copyright (C) 2022  Alexander Robinson
+
+This program is free software: you can redistribute it and/or modify
+it under the terms of the GNU General Public License as published by
+the Free Software Foundation, either version 3 of the License, or
+(at your option) any later version.
+
+This program is distributed in the hope that it will be useful,
+but WITHOUT ANY WARRANTY; without even the implied warranty of
+MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+GNU General Public License for more details.
+
+You should have received a copy of the GNU General Public License
+along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+/// A named way of building `T` from a JSON value.
+pub(crate) type Variant<T> = (&'static str, fn(Value) -> Result<T, String>);
+
+/// Deserializes the first variant that accepts the input, like
+/// `#[serde(untagged)]`, but reports why every variant was rejected.
+pub(crate) fn deserialize_untagged<'de, D, T>(
+    deserializer: D,
+    name: &str,
+    variants: &[Variant<T>],
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Value::deserialize(deserializer)?;
+
+    let mut reasons = Vec::with_capacity(variants.len());
+    for (variant, build) in variants {
+        match build(value.clone()) {
+            Ok(item) => return Ok(item),
+            Err(reason) => reasons.push(format!("{variant}: {reason}")),
+        }
+    }
+
+    Err(D::Error::custom(format!(
+        "data did not match any variant of {name} ({})",
+        reasons.join("; ")
+    )))
+}
+
+/// Deserializes a variant's contents, prefixing errors with the path inside it.
+pub(crate) fn variant<T: DeserializeOwned>(value: Value) -> Result<T, String> {
+    serde_path_to_error::deserialize(value).map_err(|e| {
+        let path = e.path().to_string();
+        if path == "." {
+            e.into_inner().to_string()
+        } else {
+            format!("{path}: {}", e.into_inner())
+        }
+    })
+}
@@ -1,8 +1,51 @@
-use bim_sort::models::Bim;
+use std::process::Command;
+
+use bim_sort::models::{Bim, ReadOptions};
+
+const BIM_WITH_ROOT_ERROR: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/tests/bim_with_root_error.bim");
 
 #[test]
 #[should_panic]
 fn throws_error_on_unknown_fields_in_root() {
-    let path = std::path::PathBuf::from("./bim_with_root_error.bim");
+    let path = std::path::PathBuf::from(BIM_WITH_ROOT_ERROR);
     Bim::from_file(&path).unwrap();
 }
+
+#[test]
+fn preserves_unknown_fields_in_root_on_request() {
+    let path = std::path::PathBuf::from(BIM_WITH_ROOT_ERROR);
+    let options = ReadOptions {
+        preserve_unknown: true,
+    };
+
+    let mut bim = Bim::from_file_with(&path, options).unwrap();
+    bim.sort();
+
+    assert!(bim
+        .to_json()
+        .unwrap()
+        .contains(r#""UNKNOWN_FIELD": "I AM UNKNOWN""#));
+}
+
+#[test]
+fn exits_with_usage_error_when_no_bim_files_are_found() {
+    let empty = std::env::temp_dir().join(format!("bim_sort_no_files_{}", std::process::id()));
+    std::fs::create_dir_all(&empty).unwrap();
+
+    let run = |args: &[&std::ffi::OsStr]| {
+        Command::new(env!("CARGO_BIN_EXE_bim_sort"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let check = run(&["--check".as_ref()]);
+    let empty_dir = run(&["--check".as_ref(), empty.as_os_str()]);
+    let scrub = run(&["scrub".as_ref(), empty.as_os_str()]);
+    std::fs::remove_dir_all(&empty).unwrap();
+
+    for output in [check, empty_dir, scrub] {
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("no bim files"));
+    }
+}